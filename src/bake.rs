@@ -0,0 +1,75 @@
+use std::f32::consts::PI;
+use std::io;
+use nalgebra_glm::{Vec2, Vec3, Mat4};
+use rayon::prelude::*;
+use fastnoise_lite::FastNoiseLite;
+use crate::cli::Options;
+use crate::color::Color;
+use crate::fragment::Fragment;
+use crate::headless::frame_path;
+use crate::image_io::save_buffer;
+use crate::light::Light;
+use crate::shaders::ShaderRegistry;
+use crate::{Uniforms, FRAME_TIME};
+
+// Evalúa el fragment shader sobre la esfera unitaria y guarda el resultado como
+// textura equirectangular (longitud en x, latitud en y), con el mismo mapeo UV
+// que `assets/sphere.obj`, así que la imagen se puede volver a aplicar con `--texture`
+pub fn run(options: &Options, shaders: &ShaderRegistry, noise: &FastNoiseLite) -> io::Result<()> {
+    let shader = shaders.get(&options.shader).expect("Unknown shader");
+    let (width, height) = (options.width, options.height);
+    let mut buffer = vec![0u32; width * height];
+
+    for frame in 0..options.frames {
+        let time = options.start_time + frame as f32 * FRAME_TIME;
+        let uniforms = bake_uniforms(options, time);
+
+        buffer.par_chunks_mut(width).enumerate().for_each(|(y, row)| {
+            for (x, pixel) in row.iter_mut().enumerate() {
+                let fragment = surface_fragment(x, y, width, height);
+                let color = shader.fragment_shader(&fragment, &uniforms, noise);
+                *pixel = options.tone_mapping.apply(color);
+            }
+        });
+
+        let path = frame_path(&options.output, frame, options.frames);
+        save_buffer(&path, width, height, &buffer)?;
+        println!("Baked {} ({}x{}, time {:.3}s)", path, width, height, time);
+    }
+
+    Ok(())
+}
+
+// Sin transformaciones: el espacio de objeto coincide con el de mundo
+fn bake_uniforms(options: &Options, time: f32) -> Uniforms {
+    Uniforms {
+        model_matrix: Mat4::identity(),
+        view_matrix: Mat4::identity(),
+        projection_matrix: Mat4::identity(),
+        viewport_matrix: Mat4::identity(),
+        time,
+        camera_position: Vec3::new(0.0, 0.0, 85.0),
+        lights: if options.lights.is_empty() { vec![Light::default()] } else { options.lights.clone() },
+    }
+}
+
+// Punto de la esfera en el centro del texel (x, y); v = 0 es la fila de abajo
+fn surface_fragment(x: usize, y: usize, width: usize, height: usize) -> Fragment {
+    let u = (x as f32 + 0.5) / width as f32;
+    let v = 1.0 - (y as f32 + 0.5) / height as f32;
+
+    let longitude = (u - 0.5) * 2.0 * PI;
+    let latitude = (v - 0.5) * PI;
+    let point = Vec3::new(
+        latitude.cos() * longitude.sin(),
+        latitude.sin(),
+        latitude.cos() * longitude.cos(),
+    );
+
+    let mut fragment = Fragment::new(Vec2::new(x as f32, y as f32), 0.0, Color::from_float(1.0, 1.0, 1.0), point, point, point);
+    fragment.tex_coords = Vec2::new(u, v);
+    fragment.uv_dx = Vec2::new(1.0 / width as f32, 0.0);
+    fragment.uv_dy = Vec2::new(0.0, -1.0 / height as f32);
+    fragment.barycentric = Vec3::new(1.0, 0.0, 0.0);
+    fragment
+}
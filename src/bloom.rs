@@ -0,0 +1,159 @@
+use rayon::prelude::*;
+use crate::color::Color;
+use crate::framebuffer::Framebuffer;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Bloom {
+    pub enabled: bool,
+    // Luminancia lineal a partir de la cual un píxel empieza a brillar
+    pub threshold: f32,
+    pub intensity: f32,
+    // Cantidad de niveles reducidos a la mitad que se difuminan y combinan
+    pub levels: usize,
+}
+
+impl Default for Bloom {
+    fn default() -> Self {
+        Bloom {
+            enabled: true,
+            threshold: 0.9,
+            intensity: 0.8,
+            levels: 5,
+        }
+    }
+}
+
+impl Bloom {
+    // Se aplica sobre `hdr_buffer`, después de `resolve` y antes del tone mapping
+    pub fn apply(&self, framebuffer: &mut Framebuffer) {
+        if !self.enabled || self.levels == 0 || self.intensity <= 0.0 {
+            return;
+        }
+
+        let source = Image {
+            width: framebuffer.width,
+            height: framebuffer.height,
+            pixels: framebuffer.hdr_buffer.clone(),
+        };
+
+        // Extraer las zonas brillantes a media resolución y construir la cadena
+        let mut chain = vec![source.bright_pass(self.threshold).downsample()];
+        while chain.len() < self.levels {
+            let last = chain.last().unwrap();
+            if last.width <= 2 || last.height <= 2 {
+                break;
+            }
+            chain.push(last.downsample());
+        }
+
+        // Difuminar cada nivel y sumarlo, del más pequeño al más grande
+        let mut glow = chain.pop().unwrap().blur();
+        while let Some(level) = chain.pop() {
+            let mut level = level.blur();
+            level.add_upsampled(&glow, 1.0);
+            glow = level;
+        }
+
+        let mut result = source;
+        result.add_upsampled(&glow, self.intensity / self.levels as f32);
+        framebuffer.hdr_buffer = result.pixels;
+    }
+}
+
+struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<Color>,
+}
+
+impl Image {
+    fn get(&self, x: isize, y: isize) -> Color {
+        let x = x.clamp(0, self.width as isize - 1) as usize;
+        let y = y.clamp(0, self.height as isize - 1) as usize;
+        self.pixels[y * self.width + x]
+    }
+
+    // Conserva solo la energía por encima del umbral, con una rampa suave. Un
+    // píxel no finito se trata como negro: el desenfoque lo extendería por toda la cadena
+    fn bright_pass(&self, threshold: f32) -> Image {
+        let pixels = self
+            .pixels
+            .iter()
+            .map(|&color| {
+                let luminance = color.luminance();
+                if !luminance.is_finite() || luminance <= threshold {
+                    Color::black()
+                } else {
+                    color * ((luminance - threshold) / luminance)
+                }
+            })
+            .collect();
+
+        Image { width: self.width, height: self.height, pixels }
+    }
+
+    // Promedio de bloques de 2x2
+    fn downsample(&self) -> Image {
+        let width = self.width.div_ceil(2);
+        let height = self.height.div_ceil(2);
+        let mut pixels = Vec::with_capacity(width * height);
+
+        for y in 0..height as isize {
+            for x in 0..width as isize {
+                let sum = self.get(x * 2, y * 2)
+                    + self.get(x * 2 + 1, y * 2)
+                    + self.get(x * 2, y * 2 + 1)
+                    + self.get(x * 2 + 1, y * 2 + 1);
+                pixels.push(sum * 0.25);
+            }
+        }
+
+        Image { width, height, pixels }
+    }
+
+    // Gaussiano separable de 5 muestras (1 4 6 4 1)
+    fn blur(&self) -> Image {
+        const WEIGHTS: [f32; 5] = [1.0 / 16.0, 4.0 / 16.0, 6.0 / 16.0, 4.0 / 16.0, 1.0 / 16.0];
+
+        let pass = |image: &Image, dx: isize, dy: isize| {
+            let mut pixels = vec![Color::black(); image.pixels.len()];
+            pixels.par_chunks_mut(image.width).enumerate().for_each(|(y, row)| {
+                for (x, pixel) in row.iter_mut().enumerate() {
+                    for (i, weight) in WEIGHTS.iter().enumerate() {
+                        let offset = i as isize - 2;
+                        *pixel = *pixel + image.get(x as isize + offset * dx, y as isize + offset * dy) * *weight;
+                    }
+                }
+            });
+            Image { width: image.width, height: image.height, pixels }
+        };
+
+        let horizontal = pass(self, 1, 0);
+        pass(&horizontal, 0, 1)
+    }
+
+    // Suma `other` (de menor resolución) escalado con interpolación bilineal
+    fn add_upsampled(&mut self, other: &Image, scale: f32) {
+        let ratio_x = other.width as f32 / self.width as f32;
+        let ratio_y = other.height as f32 / self.height as f32;
+
+        self.pixels.par_chunks_mut(self.width).enumerate().for_each(|(y, row)| {
+            let sy = (y as f32 + 0.5) * ratio_y - 0.5;
+            let y0 = sy.floor();
+            let ty = sy - y0;
+
+            for (x, pixel) in row.iter_mut().enumerate() {
+                let sx = (x as f32 + 0.5) * ratio_x - 0.5;
+                let x0 = sx.floor();
+                let tx = sx - x0;
+                let (x0, y0) = (x0 as isize, y0 as isize);
+
+                let top = other.get(x0, y0) * (1.0 - tx) + other.get(x0 + 1, y0) * tx;
+                let bottom = other.get(x0, y0 + 1) * (1.0 - tx) + other.get(x0 + 1, y0 + 1) * tx;
+                let sample = top * (1.0 - ty) + bottom * ty;
+
+                *pixel = *pixel + sample * scale;
+            }
+        });
+    }
+}
@@ -0,0 +1,413 @@
+use nalgebra_glm::Vec3;
+use fastnoise_lite::{CellularDistanceFunction, CellularReturnType, FastNoiseLite, NoiseType};
+use crate::color::Color;
+use crate::fragment::Fragment;
+use crate::light::{blinn_phong, lambert, Material};
+use crate::shaders::Shader;
+use crate::Uniforms;
+
+// Shaders procedurales de cuerpos celestes. Igual que la estrella, todos leen el
+// ruido en la posición del objeto (la esfera unitaria), así el patrón gira con
+// el cuerpo; a diferencia de ella, no emiten luz y se iluminan con la escena.
+
+fn sample(noise: &FastNoiseLite, point: Vec3, scale: f32) -> f32 {
+    noise.get_noise_3d(point.x * scale, point.y * scale, point.z * scale)
+}
+
+fn mix(a: Color, b: Color, t: f32) -> Color {
+    a * (1.0 - t) + b * t
+}
+
+fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+fn shade(fragment: &Fragment, uniforms: &Uniforms, material: Material) -> Color {
+    blinn_phong(&uniforms.lights, &fragment.world_position, &fragment.normal, &uniforms.camera_position, &material)
+}
+
+// Planeta rocoso con tierras altas y bajas y cráteres de dos tamaños
+#[derive(Debug, Clone, Copy)]
+pub struct RockyParams {
+    pub lowland: Color,
+    pub highland: Color,
+    pub crater_floor: Color,
+    pub terrain_scale: f32,
+    // Celdas por unidad del patrón de cráteres grandes; los pequeños usan el triple
+    pub crater_scale: f32,
+    // Radio de un cráter como fracción de su celda (0-1)
+    pub crater_size: f32,
+}
+
+impl Default for RockyParams {
+    fn default() -> Self {
+        RockyParams {
+            lowland: Color::new(92, 84, 78),
+            highland: Color::new(168, 158, 146),
+            crater_floor: Color::new(58, 54, 52),
+            terrain_scale: 1.2,
+            crater_scale: 2.5,
+            crater_size: 0.35,
+        }
+    }
+}
+
+pub struct RockyShader {
+    pub params: RockyParams,
+    // Ruido celular: la distancia al punto característico más cercano da la forma del cráter
+    craters: FastNoiseLite,
+}
+
+impl RockyShader {
+    pub fn new(params: RockyParams) -> Self {
+        let mut craters = FastNoiseLite::with_seed(7);
+        craters.set_noise_type(Some(NoiseType::Cellular));
+        craters.set_cellular_distance_function(Some(CellularDistanceFunction::Euclidean));
+        craters.set_cellular_return_type(Some(CellularReturnType::Distance));
+        craters.set_frequency(Some(1.0));
+        RockyShader { params, craters }
+    }
+
+    // Oscurece el fondo del cráter y aclara el borde
+    fn crater(&self, albedo: Color, point: Vec3, scale: f32) -> Color {
+        let p = &self.params;
+        let distance = sample(&self.craters, point, scale) + 1.0;
+        let floor = 1.0 - smoothstep(p.crater_size * 0.6, p.crater_size, distance);
+        let rim = 1.0 - ((distance - p.crater_size) / (p.crater_size * 0.3)).abs().min(1.0);
+        mix(albedo, p.crater_floor, floor * 0.7) * (1.0 + rim * 0.35)
+    }
+}
+
+impl Shader for RockyShader {
+    fn fragment_shader(&self, fragment: &Fragment, uniforms: &Uniforms, noise: &FastNoiseLite) -> Color {
+        let p = &self.params;
+        let point = fragment.vertex_position.normalize();
+
+        let terrain = sample(noise, point, p.terrain_scale);
+        let grain = sample(noise, point, p.terrain_scale * 8.0);
+        let albedo = mix(p.lowland, p.highland, smoothstep(-0.25, 0.35, terrain)) * (1.0 + grain * 0.15);
+
+        let albedo = self.crater(albedo, point, p.crater_scale);
+        let albedo = self.crater(albedo, point + Vec3::new(17.0, 0.0, 0.0), p.crater_scale * 3.0);
+
+        shade(fragment, uniforms, Material { albedo, ambient: 0.03, specular: 0.05, shininess: 8.0 })
+    }
+}
+
+// Gigante gaseoso con bandas de latitud turbulentas, rotación diferencial y una tormenta
+#[derive(Debug, Clone, Copy)]
+pub struct GasGiantParams {
+    // Colores que se repiten de banda en banda
+    pub band_colors: [Color; 4],
+    // Bandas entre el polo sur y el norte
+    pub bands: f32,
+    pub turbulence: f32,
+    // Radianes por segundo de diferencia entre el ecuador y las latitudes medias
+    pub flow_speed: f32,
+    pub storm_color: Color,
+    // Latitud (-1 a 1, como `y` en la esfera) y radio de la tormenta; radio 0 la desactiva
+    pub storm_latitude: f32,
+    pub storm_size: f32,
+}
+
+impl Default for GasGiantParams {
+    fn default() -> Self {
+        GasGiantParams {
+            band_colors: [
+                Color::new(222, 204, 172),
+                Color::new(176, 128, 88),
+                Color::new(236, 226, 208),
+                Color::new(150, 104, 76),
+            ],
+            bands: 14.0,
+            turbulence: 0.35,
+            flow_speed: 0.08,
+            storm_color: Color::new(190, 92, 60),
+            storm_latitude: -0.35,
+            storm_size: 0.16,
+        }
+    }
+}
+
+pub struct GasGiantShader {
+    pub params: GasGiantParams,
+}
+
+impl Shader for GasGiantShader {
+    fn fragment_shader(&self, fragment: &Fragment, uniforms: &Uniforms, noise: &FastNoiseLite) -> Color {
+        let p = &self.params;
+        let point = fragment.vertex_position.normalize();
+        let latitude = point.y;
+
+        // Cada latitud gira a su propia velocidad alrededor del eje
+        let shear = uniforms.time * p.flow_speed * (latitude * p.bands * 0.5).cos();
+        let (sin_s, cos_s) = shear.sin_cos();
+        let flowed = Vec3::new(point.x * cos_s - point.z * sin_s, point.y, point.x * sin_s + point.z * cos_s);
+
+        // Ruido estirado en longitud para que la turbulencia siga las bandas
+        let warp = noise.get_noise_3d(flowed.x * 2.0, flowed.y * 9.0, flowed.z * 2.0);
+        let detail = noise.get_noise_3d(flowed.x * 6.0, flowed.y * 24.0, flowed.z * 6.0);
+        let band = (latitude + 1.0) * 0.5 * p.bands + warp * p.turbulence * 2.0 + detail * 0.1;
+
+        let count = p.band_colors.len();
+        let position = band.rem_euclid(count as f32);
+        let index = position.floor() as usize;
+        let t = smoothstep(0.2, 0.8, position.fract());
+        let mut albedo = mix(p.band_colors[index % count], p.band_colors[(index + 1) % count], t);
+
+        // Tormenta ovalada, el doble de ancha que alta, con remolino interior
+        if p.storm_size > 0.0 {
+            let longitude = flowed.x.atan2(flowed.z);
+            let offset_y = latitude - p.storm_latitude;
+            let distance = ((longitude * 0.5).powi(2) + offset_y.powi(2)).sqrt() / p.storm_size;
+            if distance < 1.0 {
+                let swirl = (distance * 12.0 - longitude * 3.0 + detail * 2.0).sin() * 0.5 + 0.5;
+                let storm = mix(p.storm_color, p.storm_color * 1.3, swirl);
+                albedo = mix(albedo, storm, 1.0 - smoothstep(0.7, 1.0, distance));
+            }
+        }
+
+        shade(fragment, uniforms, Material { albedo, ambient: 0.03, specular: 0.08, shininess: 12.0 })
+    }
+}
+
+// Luna helada con grietas oscuras y escarcha brillante
+#[derive(Debug, Clone, Copy)]
+pub struct IcyParams {
+    pub ice: Color,
+    pub crack: Color,
+    pub crack_scale: f32,
+    // Ancho de las grietas en unidades de ruido
+    pub crack_width: f32,
+    // Variación de brillo de la escarcha
+    pub frost: f32,
+}
+
+impl Default for IcyParams {
+    fn default() -> Self {
+        IcyParams {
+            ice: Color::new(226, 236, 246),
+            crack: Color::new(132, 96, 78),
+            crack_scale: 2.0,
+            crack_width: 0.04,
+            frost: 0.2,
+        }
+    }
+}
+
+pub struct IcyShader {
+    pub params: IcyParams,
+    // Una sola octava: sus cruces por cero son líneas suaves, el fractal daría manchas
+    cracks: FastNoiseLite,
+}
+
+impl IcyShader {
+    pub fn new(params: IcyParams) -> Self {
+        let mut cracks = FastNoiseLite::with_seed(11);
+        cracks.set_noise_type(Some(NoiseType::OpenSimplex2));
+        cracks.set_frequency(Some(1.0));
+        IcyShader { params, cracks }
+    }
+}
+
+impl Shader for IcyShader {
+    fn fragment_shader(&self, fragment: &Fragment, uniforms: &Uniforms, noise: &FastNoiseLite) -> Color {
+        let p = &self.params;
+        let point = fragment.vertex_position.normalize();
+
+        // Las grietas son los cruces por cero de dos capas de ruido
+        let first = sample(&self.cracks, point, p.crack_scale).abs();
+        let second = sample(&self.cracks, point + Vec3::new(0.0, 31.0, 0.0), p.crack_scale * 2.3).abs();
+        let crack = (1.0 - smoothstep(0.0, p.crack_width, first))
+            .max((1.0 - smoothstep(0.0, p.crack_width * 0.6, second)) * 0.6);
+
+        let frost = sample(noise, point, p.crack_scale * 6.0);
+        let albedo = mix(p.ice * (1.0 + frost * p.frost), p.crack, crack);
+
+        shade(fragment, uniforms, Material { albedo, ambient: 0.05, specular: 0.6 * (1.0 - crack), shininess: 64.0 })
+    }
+}
+
+// Mundo de lava: costra oscura iluminada por la escena y venas que emiten luz propia
+#[derive(Debug, Clone, Copy)]
+pub struct LavaParams {
+    pub crust: Color,
+    pub lava_cool: Color,
+    pub lava_hot: Color,
+    pub flow_scale: f32,
+    pub vein_width: f32,
+    // Intensidad de la emisión; por encima de 1 alimenta el bloom
+    pub glow: f32,
+    pub flow_speed: f32,
+}
+
+impl Default for LavaParams {
+    fn default() -> Self {
+        LavaParams {
+            crust: Color::new(46, 36, 34),
+            lava_cool: Color::new(170, 30, 8),
+            lava_hot: Color::new(255, 190, 60),
+            flow_scale: 1.4,
+            vein_width: 0.12,
+            glow: 2.0,
+            flow_speed: 0.05,
+        }
+    }
+}
+
+pub struct LavaShader {
+    pub params: LavaParams,
+}
+
+impl Shader for LavaShader {
+    fn fragment_shader(&self, fragment: &Fragment, uniforms: &Uniforms, noise: &FastNoiseLite) -> Color {
+        let p = &self.params;
+        let point = fragment.vertex_position.normalize();
+        let drift = uniforms.time * p.flow_speed;
+
+        let flow = noise.get_noise_3d(point.x * p.flow_scale, point.y * p.flow_scale, point.z * p.flow_scale + drift);
+        let vein = 1.0 - smoothstep(0.0, p.vein_width, flow.abs());
+
+        // El calor late lentamente a lo largo de las venas
+        let heat = sample(noise, point + Vec3::new(0.0, 0.0, drift * 3.0), p.flow_scale * 3.0) * 0.5 + 0.5;
+        let lava = mix(p.lava_cool, p.lava_hot, (heat * vein).clamp(0.0, 1.0)) * p.glow;
+
+        let rough = sample(noise, point, p.flow_scale * 10.0);
+        let crust = shade(fragment, uniforms, Material {
+            albedo: p.crust * (1.0 + rough * 0.3),
+            ambient: 0.03,
+            specular: 0.1,
+            shininess: 16.0,
+        });
+
+        crust * (1.0 - vein) + lava * vein
+    }
+}
+
+// Planeta oceánico con continentes, casquetes polares y reflejo especular solo en el agua
+#[derive(Debug, Clone, Copy)]
+pub struct OceanParams {
+    pub deep: Color,
+    pub shallow: Color,
+    pub land: Color,
+    pub mountain: Color,
+    pub ice_cap: Color,
+    // Altura del ruido (-1 a 1) bajo la cual hay agua
+    pub sea_level: f32,
+    pub continent_scale: f32,
+    // Latitud (0-1) desde la que empiezan los casquetes
+    pub polar_cap: f32,
+}
+
+impl Default for OceanParams {
+    fn default() -> Self {
+        OceanParams {
+            deep: Color::new(10, 34, 92),
+            shallow: Color::new(30, 104, 150),
+            land: Color::new(70, 116, 52),
+            mountain: Color::new(120, 100, 76),
+            ice_cap: Color::new(240, 244, 250),
+            sea_level: 0.05,
+            continent_scale: 0.9,
+            polar_cap: 0.82,
+        }
+    }
+}
+
+pub struct OceanShader {
+    pub params: OceanParams,
+}
+
+impl Shader for OceanShader {
+    fn fragment_shader(&self, fragment: &Fragment, uniforms: &Uniforms, noise: &FastNoiseLite) -> Color {
+        let p = &self.params;
+        let point = fragment.vertex_position.normalize();
+
+        let height = sample(noise, point, p.continent_scale) * 0.8 + sample(noise, point, p.continent_scale * 4.0) * 0.2;
+        let edge = sample(noise, point, 5.0) * 0.06;
+
+        let material = if point.y.abs() > p.polar_cap + edge {
+            Material { albedo: p.ice_cap, ambient: 0.05, specular: 0.3, shininess: 32.0 }
+        } else if height < p.sea_level {
+            let shore = smoothstep(p.sea_level - 0.3, p.sea_level, height);
+            Material { albedo: mix(p.deep, p.shallow, shore), ambient: 0.03, specular: 0.8, shininess: 96.0 }
+        } else {
+            let altitude = smoothstep(p.sea_level, p.sea_level + 0.45, height);
+            Material { albedo: mix(p.land, p.mountain, altitude), ambient: 0.03, specular: 0.04, shininess: 8.0 }
+        };
+
+        shade(fragment, uniforms, material)
+    }
+}
+
+// Anillos planetarios translúcidos sobre `mesh::annulus`: la densidad de las
+// bandas depende solo de la u radial, así que el giro del anillo no se nota
+#[derive(Debug, Clone, Copy)]
+pub struct RingParams {
+    pub inner_color: Color,
+    pub outer_color: Color,
+    // Bandas de ruido a lo ancho del anillo
+    pub band_scale: f32,
+    // Opacidad de las zonas más densas
+    pub opacity: f32,
+    // División vacía, como la de Cassini, en coordenada radial (0-1)
+    pub gap_position: f32,
+    pub gap_width: f32,
+}
+
+impl Default for RingParams {
+    fn default() -> Self {
+        RingParams {
+            inner_color: Color::new(150, 134, 112),
+            outer_color: Color::new(214, 198, 170),
+            band_scale: 6.0,
+            opacity: 0.85,
+            gap_position: 0.62,
+            gap_width: 0.04,
+        }
+    }
+}
+
+pub struct RingShader {
+    pub params: RingParams,
+}
+
+impl RingShader {
+    fn density(&self, radial: f32, noise: &FastNoiseLite) -> f32 {
+        let p = &self.params;
+        let coarse = noise.get_noise_3d(radial * p.band_scale, 0.0, 0.0);
+        let fine = noise.get_noise_3d(radial * p.band_scale * 4.0, 3.7, 0.0);
+        let bands = (0.55 + coarse * 0.35 + fine * 0.2).clamp(0.0, 1.0);
+
+        let gap = smoothstep(p.gap_width * 0.5, p.gap_width, (radial - p.gap_position).abs());
+        let edges = smoothstep(0.0, 0.04, radial) * (1.0 - smoothstep(0.9, 1.0, radial));
+        bands * gap * edges
+    }
+}
+
+impl Shader for RingShader {
+    fn fragment_shader(&self, fragment: &Fragment, uniforms: &Uniforms, noise: &FastNoiseLite) -> Color {
+        let p = &self.params;
+        let radial = fragment.tex_coords.x;
+        let grain = noise.get_noise_3d(radial * p.band_scale * 10.0, 7.1, 0.0);
+        let albedo = mix(p.inner_color, p.outer_color, radial) * (0.85 + grain * 0.15);
+
+        // La cara iluminada refleja y la opuesta deja pasar parte de la luz
+        let lit = lambert(&uniforms.lights, &fragment.world_position, &fragment.normal, albedo);
+        let transmitted = lambert(&uniforms.lights, &fragment.world_position, &-fragment.normal, albedo);
+        albedo * 0.04 + lit + transmitted * 0.35
+    }
+
+    fn is_transparent(&self) -> bool {
+        true
+    }
+
+    fn fragment_alpha(&self, fragment: &Fragment, _uniforms: &Uniforms, noise: &FastNoiseLite) -> f32 {
+        self.density(fragment.tex_coords.x, noise) * self.params.opacity
+    }
+
+    fn double_sided(&self) -> bool {
+        true
+    }
+}
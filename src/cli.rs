@@ -0,0 +1,150 @@
+use crate::culling::RasterState;
+use crate::framebuffer::Antialiasing;
+use crate::tonemap::ToneMapping;
+use crate::bloom::Bloom;
+use crate::postprocess::DEFAULT_CONFIG;
+use crate::debug::DebugMode;
+use crate::light::Light;
+use crate::texture::Sampler;
+use crate::mesh::MeshSource;
+
+pub struct Options {
+    pub headless: bool,
+    pub bake: bool,
+    pub frames: u32,
+    pub start_time: f32,
+    pub output: String,
+    pub width: usize,
+    pub height: usize,
+    pub shader: String,
+    pub raster_state: RasterState,
+    pub antialiasing: Antialiasing,
+    pub tone_mapping: ToneMapping,
+    pub bloom: Bloom,
+    pub post_config: String,
+    pub debug_mode: DebugMode,
+    pub lights: Vec<Light>,
+    pub texture: Option<String>,
+    pub sampler: Sampler,
+    pub system: Option<String>,
+    pub time_scale: f32,
+    pub mesh: MeshSource,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            headless: false,
+            bake: false,
+            frames: 1,
+            start_time: 0.0,
+            output: String::from("frame.png"),
+            width: 800,
+            height: 600,
+            shader: String::from("star"),
+            raster_state: RasterState::default(),
+            antialiasing: Antialiasing::None,
+            tone_mapping: ToneMapping::default(),
+            bloom: Bloom::default(),
+            post_config: String::from(DEFAULT_CONFIG),
+            debug_mode: DebugMode::Off,
+            // Sin luces se usan las de la escena, o `Light::default()` si no tiene
+            lights: Vec::new(),
+            texture: None,
+            sampler: Sampler::default(),
+            system: None,
+            time_scale: 1.0,
+            mesh: MeshSource::default(),
+        }
+    }
+}
+
+pub const USAGE: &str = "Usage: Lab5 [--shader NAME] [--size WIDTHxHEIGHT] [--cull none|back|front] [--front-face ccw|cw]
+            [--aa none|msaa2|msaa4|msaa8|ssaa2|ssaa3|ssaa4]
+            [--tonemap clamp|reinhard|aces] [--exposure VALUE]
+            [--no-bloom] [--bloom-threshold VALUE] [--bloom-intensity VALUE] [--bloom-levels N]
+            [--post FILE] [--debug off|wireframe|flat_normals|normals|depth|barycentric|noise|overdraw]
+            [--light directional:DX,DY,DZ|point:X,Y,Z|spot:X,Y,Z:DX,DY,DZ[:color=RRGGBB][:intensity=V][:range=V][:angle=DEG]]...
+            [--texture FILE.png|FILE.ppm|FILE.tga] [--filter nearest|bilinear|trilinear|aniso2|aniso4|aniso8|aniso16] [--wrap repeat|clamp]
+            [--system FILE] [--time-scale VALUE]
+            [--mesh FILE.obj|uv_sphere[:N]|icosphere[:N]|cube_sphere[:N]|plane[:N]|torus[:N]|cylinder[:N]]
+            [--headless [--frames N] [--time SECONDS] [--output FILE.png|FILE.ppm]]
+            [--bake [--size WIDTHxHEIGHT (2:1, default 1024x512)] [--frames N] [--time SECONDS] [--output FILE.png|FILE.ppm]]";
+
+impl Options {
+    pub fn parse<I: Iterator<Item = String>>(mut args: I) -> Result<Self, String> {
+        let mut options = Options::default();
+        let mut size_given = false;
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--headless" => options.headless = true,
+                "--bake" => options.bake = true,
+                "--frames" => {
+                    options.frames = parse_value(&arg, args.next())?;
+                    if options.frames == 0 {
+                        return Err(String::from("--frames must be at least 1"));
+                    }
+                }
+                "--time" => options.start_time = parse_value(&arg, args.next())?,
+                "--output" => options.output = parse_value(&arg, args.next())?,
+                "--shader" => options.shader = parse_value(&arg, args.next())?,
+                "--cull" => options.raster_state.cull_mode = parse_value(&arg, args.next())?,
+                "--aa" => options.antialiasing = parse_value(&arg, args.next())?,
+                "--tonemap" => options.tone_mapping.operator = parse_value(&arg, args.next())?,
+                "--exposure" => options.tone_mapping.exposure = parse_value(&arg, args.next())?,
+                "--no-bloom" => options.bloom.enabled = false,
+                "--bloom-threshold" => options.bloom.threshold = parse_value(&arg, args.next())?,
+                "--bloom-intensity" => options.bloom.intensity = parse_value(&arg, args.next())?,
+                "--bloom-levels" => options.bloom.levels = parse_value(&arg, args.next())?,
+                "--post" => options.post_config = parse_value(&arg, args.next())?,
+                "--light" => options.lights.push(parse_value(&arg, args.next())?),
+                "--texture" => options.texture = Some(parse_value(&arg, args.next())?),
+                "--filter" => options.sampler.filter = parse_value(&arg, args.next())?,
+                "--wrap" => options.sampler.wrap = parse_value(&arg, args.next())?,
+                "--system" => options.system = Some(parse_value(&arg, args.next())?),
+                "--time-scale" => options.time_scale = parse_value(&arg, args.next())?,
+                // El error de la malla ya nombra el rango válido de la resolución
+                "--mesh" => options.mesh = parse_value::<String>(&arg, args.next())?.parse()?,
+                "--debug" => options.debug_mode = parse_value(&arg, args.next())?,
+                "--front-face" => options.raster_state.front_face = parse_value(&arg, args.next())?,
+                "--size" => {
+                    let size: String = parse_value(&arg, args.next())?;
+                    let (width, height) = size
+                        .split_once('x')
+                        .ok_or_else(|| format!("Invalid size: {}", size))?;
+                    options.width = parse_value(&arg, Some(width.to_string()))?;
+                    options.height = parse_value(&arg, Some(height.to_string()))?;
+                    if options.width == 0 || options.height == 0 {
+                        return Err(format!("Invalid size: {}", size));
+                    }
+                    size_given = true;
+                }
+                _ => return Err(format!("Unknown argument: {}", arg)),
+            }
+        }
+
+        if options.headless && options.bake {
+            return Err(String::from("--headless and --bake cannot be combined"));
+        }
+
+        // Un mapa equirectangular cubre 360° de longitud por 180° de latitud
+        if options.bake {
+            if !size_given {
+                options.width = 1024;
+                options.height = 512;
+            } else if options.width != options.height * 2 {
+                return Err(format!("--bake needs a 2:1 size, got {}x{}", options.width, options.height));
+            }
+        }
+
+        Ok(options)
+    }
+}
+
+fn parse_value<T: std::str::FromStr>(flag: &str, value: Option<String>) -> Result<T, String> {
+    let value = value.ok_or_else(|| format!("Missing value for {}", flag))?;
+    value
+        .parse()
+        .map_err(|_| format!("Invalid value for {}: {}", flag, value))
+}
@@ -0,0 +1,133 @@
+use nalgebra_glm::{Mat4, Vec3, Vec4};
+use crate::vertex::Vertex;
+
+// Los lados x/y se recortan contra una banda de guarda más amplia que la
+// pantalla; lo que quede fuera de la pantalla pero dentro de la banda lo
+// descarta el rasterizador al limitar el bounding box.
+const GUARD_BAND: f32 = 4.0;
+
+#[derive(Debug, Clone, Copy)]
+enum Plane {
+    Near,
+    Far,
+    Left,
+    Right,
+    Bottom,
+    Top,
+}
+
+const PLANES: [Plane; 6] = [Plane::Near, Plane::Far, Plane::Left, Plane::Right, Plane::Bottom, Plane::Top];
+
+// Distancia con signo al plano en espacio de recorte; >= 0 está dentro
+fn distance(plane: Plane, p: &Vec4, band: f32) -> f32 {
+    match plane {
+        Plane::Near => p.z + p.w,
+        Plane::Far => p.w - p.z,
+        Plane::Left => p.x + band * p.w,
+        Plane::Right => band * p.w - p.x,
+        Plane::Bottom => p.y + band * p.w,
+        Plane::Top => band * p.w - p.y,
+    }
+}
+
+fn band_for(plane: Plane) -> f32 {
+    match plane {
+        Plane::Near | Plane::Far => 1.0,
+        _ => GUARD_BAND,
+    }
+}
+
+// Recorta el triángulo contra el volumen de vista y agrega a `out` los
+// triángulos resultantes (0, 1 o varios en abanico), aún en espacio de recorte
+pub fn clip_triangle(v1: &Vertex, v2: &Vertex, v3: &Vertex, out: &mut Vec<Vertex>) {
+    let positions = [v1.clip_position, v2.clip_position, v3.clip_position];
+
+    // Totalmente fuera de algún plano del frustum: se descarta
+    let outside_frustum = PLANES
+        .iter()
+        .any(|&plane| positions.iter().all(|p| distance(plane, p, 1.0) < 0.0));
+    if outside_frustum {
+        return;
+    }
+
+    let needs_clipping: Vec<Plane> = PLANES
+        .iter()
+        .copied()
+        .filter(|&plane| positions.iter().any(|p| distance(plane, p, band_for(plane)) < 0.0))
+        .collect();
+
+    if needs_clipping.is_empty() {
+        out.push(v1.clone());
+        out.push(v2.clone());
+        out.push(v3.clone());
+        return;
+    }
+
+    // Sutherland–Hodgman, un plano a la vez
+    let mut polygon = vec![v1.clone(), v2.clone(), v3.clone()];
+    for plane in needs_clipping {
+        polygon = clip_polygon(&polygon, plane);
+        if polygon.len() < 3 {
+            return;
+        }
+    }
+
+    for i in 1..polygon.len() - 1 {
+        out.push(polygon[0].clone());
+        out.push(polygon[i].clone());
+        out.push(polygon[i + 1].clone());
+    }
+}
+
+fn clip_polygon(polygon: &[Vertex], plane: Plane) -> Vec<Vertex> {
+    let band = band_for(plane);
+    let mut result = Vec::with_capacity(polygon.len() + 1);
+
+    for i in 0..polygon.len() {
+        let current = &polygon[i];
+        let next = &polygon[(i + 1) % polygon.len()];
+        let d_current = distance(plane, &current.clip_position, band);
+        let d_next = distance(plane, &next.clip_position, band);
+
+        if d_current >= 0.0 {
+            result.push(current.clone());
+        }
+
+        if (d_current >= 0.0) != (d_next >= 0.0) {
+            let t = d_current / (d_current - d_next);
+            result.push(lerp_vertex(current, next, t));
+        }
+    }
+
+    result
+}
+
+// El espacio de recorte es lineal antes de dividir por w, así que todos los
+// atributos se interpolan directamente
+fn lerp_vertex(a: &Vertex, b: &Vertex, t: f32) -> Vertex {
+    Vertex {
+        position: a.position.lerp(&b.position, t),
+        normal: a.normal.lerp(&b.normal, t),
+        tex_coords: a.tex_coords.lerp(&b.tex_coords, t),
+        color: a.color.lerp(&b.color, t),
+        clip_position: a.clip_position.lerp(&b.clip_position, t),
+        world_position: a.world_position.lerp(&b.world_position, t),
+        transformed_position: Vec3::zeros(),
+        transformed_normal: a.transformed_normal.lerp(&b.transformed_normal, t),
+    }
+}
+
+// División de perspectiva y transformación de viewport
+pub fn project(vertex: &mut Vertex, viewport_matrix: &Mat4) {
+    let clip = vertex.clip_position;
+    let w = clip.w;
+    let ndc_position = Vec4::new(
+        clip.x / w,
+        clip.y / w,
+        clip.z / w,
+        1.0
+    );
+
+    let screen_position = viewport_matrix * ndc_position;
+    vertex.transformed_position = Vec3::new(screen_position.x, screen_position.y, screen_position.z);
+}
@@ -1,32 +1,61 @@
 use std::ops::{Add, Mul};
+use std::sync::OnceLock;
 
-#[derive(Debug, Clone, Copy)]
+// Color RGB lineal en punto flotante. Los canales pueden pasar de 1.0 (HDR);
+// el tone mapping los lleva al rango de pantalla al final del cuadro.
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Color {
-    pub r: u8,
-    pub g: u8,
-    pub b: u8,
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
 }
 
 impl Color {
+    // Los valores de 8 bits se interpretan como sRGB, igual que en un selector de color
     pub fn new(r: u8, g: u8, b: u8) -> Self {
-        Color { r, g, b }
+        let lut = srgb_to_linear_lut();
+        Color {
+            r: lut[r as usize],
+            g: lut[g as usize],
+            b: lut[b as usize],
+        }
     }
 
     pub fn black() -> Self {
-        Color { r: 0, g: 0, b: 0 }
+        Color { r: 0.0, g: 0.0, b: 0.0 }
     }
 
-    pub fn to_hex(&self) -> u32 {
-        ((self.r as u32) << 16) | ((self.g as u32) << 8) | (self.b as u32)
+    pub fn from_hex(hex: u32) -> Self {
+        Color::new((hex >> 16) as u8, (hex >> 8) as u8, hex as u8)
     }
 
+    // Valores lineales; no se recortan por arriba para permitir energía > 1.0
     pub fn from_float(r: f32, g: f32, b: f32) -> Self {
         Color {
-            r: (r.clamp(0.0, 1.0) * 255.0) as u8,
-            g: (g.clamp(0.0, 1.0) * 255.0) as u8,
-            b: (b.clamp(0.0, 1.0) * 255.0) as u8,
+            r: r.max(0.0),
+            g: g.max(0.0),
+            b: b.max(0.0),
         }
     }
+
+    // Codifica a sRGB de 8 bits recortando a [0, 1], sin tone mapping
+    pub fn to_hex(self) -> u32 {
+        let r = linear_to_srgb(self.r) as u32;
+        let g = linear_to_srgb(self.g) as u32;
+        let b = linear_to_srgb(self.b) as u32;
+        (r << 16) | (g << 8) | b
+    }
+
+    pub fn luminance(self) -> f32 {
+        0.2126 * self.r + 0.7152 * self.g + 0.0722 * self.b
+    }
+
+    // Canales NaN o infinitos a 0, para que un fragmento inválido no contamine
+    // el promedio, el bloom ni el tone mapping
+    pub fn finite_or_black(self) -> Color {
+        let finite = |value: f32| if value.is_finite() { value } else { 0.0 };
+        Color { r: finite(self.r), g: finite(self.g), b: finite(self.b) }
+    }
 }
 
 impl Add for Color {
@@ -34,9 +63,9 @@ impl Add for Color {
 
     fn add(self, other: Color) -> Color {
         Color {
-            r: self.r.saturating_add(other.r),
-            g: self.g.saturating_add(other.g),
-            b: self.b.saturating_add(other.b),
+            r: self.r + other.r,
+            g: self.g + other.g,
+            b: self.b + other.b,
         }
     }
 }
@@ -46,9 +75,63 @@ impl Mul<f32> for Color {
 
     fn mul(self, scalar: f32) -> Color {
         Color {
-            r: (self.r as f32 * scalar).clamp(0.0, 255.0) as u8,
-            g: (self.g as f32 * scalar).clamp(0.0, 255.0) as u8,
-            b: (self.b as f32 * scalar).clamp(0.0, 255.0) as u8,
+            r: self.r * scalar,
+            g: self.g * scalar,
+            b: self.b * scalar,
+        }
+    }
+}
+
+// Producto por componente, para filtrar la luz por el color de la superficie
+impl Mul<Color> for Color {
+    type Output = Color;
+
+    fn mul(self, other: Color) -> Color {
+        Color {
+            r: self.r * other.r,
+            g: self.g * other.g,
+            b: self.b * other.b,
         }
     }
-}
\ No newline at end of file
+}
+
+const ENCODE_LUT_SIZE: usize = 4096;
+
+fn srgb_to_linear_lut() -> &'static [f32; 256] {
+    static LUT: OnceLock<[f32; 256]> = OnceLock::new();
+    LUT.get_or_init(|| {
+        let mut lut = [0.0; 256];
+        for (i, value) in lut.iter_mut().enumerate() {
+            let c = i as f32 / 255.0;
+            *value = if c <= 0.04045 {
+                c / 12.92
+            } else {
+                ((c + 0.055) / 1.055).powf(2.4)
+            };
+        }
+        lut
+    })
+}
+
+// Tabla de codificación lineal -> sRGB, muestreada uniformemente en [0, 1]
+fn linear_to_srgb_lut() -> &'static [u8] {
+    static LUT: OnceLock<Vec<u8>> = OnceLock::new();
+    LUT.get_or_init(|| {
+        (0..ENCODE_LUT_SIZE)
+            .map(|i| {
+                let c = i as f32 / (ENCODE_LUT_SIZE - 1) as f32;
+                let encoded = if c <= 0.0031308 {
+                    c * 12.92
+                } else {
+                    1.055 * c.powf(1.0 / 2.4) - 0.055
+                };
+                (encoded * 255.0 + 0.5) as u8
+            })
+            .collect()
+    })
+}
+
+pub fn linear_to_srgb(value: f32) -> u8 {
+    let index = (value.clamp(0.0, 1.0) * (ENCODE_LUT_SIZE - 1) as f32 + 0.5) as usize;
+    linear_to_srgb_lut()[index]
+}
@@ -0,0 +1,90 @@
+use std::str::FromStr;
+use crate::vertex::Vertex;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CullMode {
+    None,
+    Back,
+    Front,
+}
+
+// Orden de los vértices (en NDC, con y hacia arriba) que define la cara frontal
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrontFace {
+    CounterClockwise,
+    Clockwise,
+}
+
+// Estado de rasterización de cada llamada a render()
+#[derive(Debug, Clone, Copy)]
+pub struct RasterState {
+    pub cull_mode: CullMode,
+    pub front_face: FrontFace,
+}
+
+impl Default for RasterState {
+    fn default() -> Self {
+        RasterState {
+            cull_mode: CullMode::Back,
+            front_face: FrontFace::CounterClockwise,
+        }
+    }
+}
+
+impl RasterState {
+    // Se evalúa después del vertex shader, con las posiciones ya recortadas
+    pub fn is_culled(&self, v1: &Vertex, v2: &Vertex, v3: &Vertex) -> bool {
+        match self.cull_mode {
+            CullMode::None => false,
+            CullMode::Back => !self.is_front_facing(v1, v2, v3),
+            CullMode::Front => self.is_front_facing(v1, v2, v3),
+        }
+    }
+
+    pub fn is_front_facing(&self, v1: &Vertex, v2: &Vertex, v3: &Vertex) -> bool {
+        let a = v1.clip_position.xy() / v1.clip_position.w;
+        let b = v2.clip_position.xy() / v2.clip_position.w;
+        let c = v3.clip_position.xy() / v3.clip_position.w;
+        let signed_area = (b.x - a.x) * (c.y - a.y) - (c.x - a.x) * (b.y - a.y);
+
+        match self.front_face {
+            FrontFace::CounterClockwise => signed_area > 0.0,
+            FrontFace::Clockwise => signed_area < 0.0,
+        }
+    }
+}
+
+impl CullMode {
+    pub fn next(self) -> Self {
+        match self {
+            CullMode::None => CullMode::Back,
+            CullMode::Back => CullMode::Front,
+            CullMode::Front => CullMode::None,
+        }
+    }
+}
+
+impl FromStr for CullMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(CullMode::None),
+            "back" => Ok(CullMode::Back),
+            "front" => Ok(CullMode::Front),
+            _ => Err(format!("Unknown cull mode: {}", s)),
+        }
+    }
+}
+
+impl FromStr for FrontFace {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ccw" => Ok(FrontFace::CounterClockwise),
+            "cw" => Ok(FrontFace::Clockwise),
+            _ => Err(format!("Unknown front face: {}", s)),
+        }
+    }
+}
@@ -0,0 +1,181 @@
+use std::str::FromStr;
+use nalgebra_glm::{Vec3, Mat3, mat4_to_mat3};
+use fastnoise_lite::FastNoiseLite;
+use crate::color::Color;
+use crate::fragment::Fragment;
+use crate::framebuffer::Framebuffer;
+use crate::shaders::Shader;
+use crate::vertex::Vertex;
+use crate::{linearize_depth, Uniforms};
+
+// Vistas de depuración para aislar cada etapa del pipeline
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DebugMode {
+    Off,
+    Wireframe,
+    FlatNormals,
+    Normals,
+    Depth,
+    Barycentric,
+    Noise,
+    Overdraw,
+}
+
+impl DebugMode {
+    // En el orden de las teclas F1-F8
+    pub const ALL: [DebugMode; 8] = [
+        DebugMode::Off,
+        DebugMode::Wireframe,
+        DebugMode::FlatNormals,
+        DebugMode::Normals,
+        DebugMode::Depth,
+        DebugMode::Barycentric,
+        DebugMode::Noise,
+        DebugMode::Overdraw,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            DebugMode::Off => "off",
+            DebugMode::Wireframe => "wireframe",
+            DebugMode::FlatNormals => "flat_normals",
+            DebugMode::Normals => "normals",
+            DebugMode::Depth => "depth",
+            DebugMode::Barycentric => "barycentric",
+            DebugMode::Noise => "noise",
+            DebugMode::Overdraw => "overdraw",
+        }
+    }
+
+    // Sustituye `hdr_buffer` por la visualización de los buffers, después de `resolve`
+    pub fn visualize(self, framebuffer: &mut Framebuffer) {
+        match self {
+            DebugMode::Depth => visualize_depth(framebuffer),
+            DebugMode::Overdraw => visualize_overdraw(framebuffer),
+            _ => {}
+        }
+    }
+}
+
+impl FromStr for DebugMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        DebugMode::ALL
+            .into_iter()
+            .find(|mode| mode.name() == s || (s == "none" && *mode == DebugMode::Off))
+            .ok_or_else(|| {
+                let names: Vec<_> = DebugMode::ALL.iter().map(|mode| mode.name()).collect();
+                format!("Unknown debug mode: {} (use {})", s, names.join(", "))
+            })
+    }
+}
+
+// Envuelve el shader activo: conserva su vertex shader (y su desplazamiento)
+// y reemplaza el color por la información de depuración
+pub struct DebugShader<'a> {
+    pub mode: DebugMode,
+    pub inner: &'a dyn Shader,
+}
+
+impl Shader for DebugShader<'_> {
+    fn vertex_shader(&self, vertex: &Vertex, uniforms: &Uniforms, noise: &FastNoiseLite) -> Vertex {
+        self.inner.vertex_shader(vertex, uniforms, noise)
+    }
+
+    fn fragment_shader(&self, fragment: &Fragment, uniforms: &Uniforms, noise: &FastNoiseLite) -> Color {
+        match self.mode {
+            DebugMode::Wireframe => {
+                // Línea de un píxel con borde suavizado sobre el relleno oscurecido
+                let fill = self.inner.fragment_shader(fragment, uniforms, noise) * 0.2;
+                let line = (1.0 - (fragment.edge_distance - 0.5)).clamp(0.0, 1.0);
+                fill * (1.0 - line) + Color::from_float(1.0, 1.0, 1.0) * line
+            }
+            DebugMode::FlatNormals => {
+                let normal_matrix = mat4_to_mat3(&uniforms.model_matrix)
+                    .transpose()
+                    .try_inverse()
+                    .unwrap_or(Mat3::identity());
+                normal_to_color((normal_matrix * fragment.face_normal).normalize())
+            }
+            DebugMode::Normals => normal_to_color(fragment.normal.normalize()),
+            DebugMode::Barycentric => {
+                let weights = fragment.barycentric;
+                Color::from_float(weights.x, weights.y, weights.z)
+            }
+            DebugMode::Noise => {
+                // Misma capa base que usa el shader de la estrella
+                let pos = fragment.vertex_position * 2.2;
+                let value = noise.get_noise_3d(pos.x, pos.y, pos.z + uniforms.time * 0.18);
+                let gray = (value + 1.0) * 0.5;
+                Color::from_float(gray, gray, gray)
+            }
+            _ => self.inner.fragment_shader(fragment, uniforms, noise),
+        }
+    }
+
+    fn modifies_depth(&self) -> bool {
+        self.inner.modifies_depth()
+    }
+
+    fn fragment_depth(&self, fragment: &Fragment, uniforms: &Uniforms, noise: &FastNoiseLite) -> f32 {
+        self.inner.fragment_depth(fragment, uniforms, noise)
+    }
+
+    // La transparencia no se delega: las vistas de depuración se dibujan opacas
+    // para que la profundidad y el overdraw incluyan todas las superficies
+    fn double_sided(&self) -> bool {
+        self.inner.double_sided()
+    }
+}
+
+// [-1, 1] -> [0, 1] por componente
+fn normal_to_color(normal: Vec3) -> Color {
+    Color::from_float(normal.x * 0.5 + 0.5, normal.y * 0.5 + 0.5, normal.z * 0.5 + 0.5)
+}
+
+// Profundidad lineal normalizada al rango visible: blanco cerca, negro lejos
+fn visualize_depth(framebuffer: &mut Framebuffer) {
+    let (near, far) = framebuffer
+        .zbuffer
+        .iter()
+        .filter(|z| z.is_finite())
+        .map(|&z| linearize_depth(z))
+        .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), d| (min.min(d), max.max(d)));
+    let range = (far - near).max(1e-6);
+
+    for (pixel, &z) in framebuffer.hdr_buffer.iter_mut().zip(&framebuffer.zbuffer) {
+        *pixel = if z.is_finite() {
+            let value = 1.0 - (linearize_depth(z) - near) / range;
+            Color::from_float(value, value, value)
+        } else {
+            Color::black()
+        };
+    }
+}
+
+// Mapa de calor: 1 fragmento azul, 2 verde, 3 amarillo, 4 o más rojo
+fn visualize_overdraw(framebuffer: &mut Framebuffer) {
+    const HEAT: [(f32, f32, f32); 5] = [
+        (0.0, 0.0, 0.0),
+        (0.0, 0.1, 1.0),
+        (0.0, 1.0, 0.1),
+        (1.0, 1.0, 0.0),
+        (1.0, 0.0, 0.0),
+    ];
+
+    // Con SSAA se toma el máximo del bloque de muestras de cada píxel
+    let scale = framebuffer.raster_width / framebuffer.width;
+    for y in 0..framebuffer.height {
+        for x in 0..framebuffer.width {
+            let mut count = 0;
+            for sy in 0..scale {
+                let row = (y * scale + sy) * framebuffer.raster_width + x * scale;
+                count = framebuffer.overdraw[row..row + scale].iter().fold(count, |max, &c| max.max(c));
+            }
+
+            let (r, g, b) = HEAT[(count as usize).min(HEAT.len() - 1)];
+            framebuffer.hdr_buffer[y * framebuffer.width + x] = Color::from_float(r, g, b);
+        }
+    }
+}
@@ -1,5 +1,6 @@
 use nalgebra_glm::{Vec2, Vec3};
 use crate::color::Color;
+use crate::framebuffer::MAX_SAMPLES;
 
 #[derive(Clone)]
 pub struct Fragment {
@@ -8,18 +9,38 @@ pub struct Fragment {
     pub color: Color,
     pub normal: Vec3,
     pub vertex_position: Vec3,
-    pub intensity: f32,
+    pub world_position: Vec3,
+    pub tex_coords: Vec2,
+    // Cambio de las UV por píxel en x y en y
+    pub uv_dx: Vec2,
+    pub uv_dy: Vec2,
+    // Muestras cubiertas (bit por muestra) y su profundidad, para MSAA
+    pub coverage: u32,
+    pub sample_depths: [f32; MAX_SAMPLES],
+    // Datos para los modos de depuración: pesos de cada vértice, normal de la
+    // cara en espacio de objeto y distancia en píxeles a la arista más cercana
+    pub barycentric: Vec3,
+    pub face_normal: Vec3,
+    pub edge_distance: f32,
 }
 
 impl Fragment {
-    pub fn new(position: Vec2, depth: f32, color: Color, normal: Vec3, vertex_position: Vec3, intensity: f32) -> Self {
+    pub fn new(position: Vec2, depth: f32, color: Color, normal: Vec3, vertex_position: Vec3, world_position: Vec3) -> Self {
         Fragment {
             position,
             depth,
             color,
             normal,
             vertex_position,
-            intensity,
+            world_position,
+            tex_coords: Vec2::zeros(),
+            uv_dx: Vec2::zeros(),
+            uv_dy: Vec2::zeros(),
+            coverage: 1,
+            sample_depths: [depth; MAX_SAMPLES],
+            barycentric: Vec3::zeros(),
+            face_normal: normal,
+            edge_distance: f32::INFINITY,
         }
     }
 }
\ No newline at end of file
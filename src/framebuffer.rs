@@ -1,48 +1,190 @@
+use std::str::FromStr;
+use rayon::prelude::*;
+use crate::color::Color;
+use crate::tonemap::ToneMapping;
+
+pub const MAX_SAMPLES: usize = 8;
+
+// Posiciones de muestra dentro del píxel en 1/16 de píxel (patrones estándar
+// de D3D), medidas desde la esquina superior izquierda
+const SAMPLES_1: [(i32, i32); 1] = [(8, 8)];
+const SAMPLES_2: [(i32, i32); 2] = [(12, 12), (4, 4)];
+const SAMPLES_4: [(i32, i32); 4] = [(6, 2), (14, 6), (2, 10), (10, 14)];
+const SAMPLES_8: [(i32, i32); 8] = [(9, 5), (7, 11), (13, 9), (5, 3), (3, 13), (1, 7), (11, 15), (15, 1)];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Antialiasing {
+    None,
+    // Cobertura y profundidad por muestra, sombreado una vez por píxel
+    Msaa(usize),
+    // Render a mayor resolución (factor por eje) y reducción por promedio
+    Ssaa(usize),
+}
+
+impl Antialiasing {
+    pub fn next(self) -> Self {
+        match self {
+            Antialiasing::None => Antialiasing::Msaa(4),
+            Antialiasing::Msaa(_) => Antialiasing::Ssaa(2),
+            Antialiasing::Ssaa(_) => Antialiasing::None,
+        }
+    }
+}
+
+impl FromStr for Antialiasing {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid antialiasing mode: {} (use none, msaa2/4/8 or ssaa2/3/4)", s);
+
+        if s == "none" {
+            Ok(Antialiasing::None)
+        } else if let Some(count) = s.strip_prefix("msaa") {
+            match count.parse().map_err(|_| invalid())? {
+                count @ (1 | 2 | 4 | 8) => Ok(Antialiasing::Msaa(count)),
+                _ => Err(invalid()),
+            }
+        } else if let Some(factor) = s.strip_prefix("ssaa") {
+            match factor.parse().map_err(|_| invalid())? {
+                factor @ 1..=4 => Ok(Antialiasing::Ssaa(factor)),
+                _ => Err(invalid()),
+            }
+        } else {
+            Err(invalid())
+        }
+    }
+}
+
 pub struct Framebuffer {
     pub width: usize,
     pub height: usize,
+    // Salida para pantalla o archivo, ya con tone mapping
     pub buffer: Vec<u32>,
+    // Color lineal HDR por píxel después de resolver las muestras
+    pub hdr_buffer: Vec<Color>,
+    // Profundidad más cercana de cada píxel después de resolver
     pub zbuffer: Vec<f32>,
-    background_color: u32,
-    current_color: u32,
+    // Resolución a la que se rasteriza (mayor que la de salida con SSAA)
+    pub raster_width: usize,
+    pub raster_height: usize,
+    pub sample_count: usize,
+    pub color_samples: Vec<Color>,
+    pub depth_samples: Vec<f32>,
+    // Fragmentos sombreados por píxel de rasterización en el cuadro actual
+    pub overdraw: Vec<u32>,
+    antialiasing: Antialiasing,
+    background_color: Color,
 }
 
 impl Framebuffer {
     pub fn new(width: usize, height: usize) -> Self {
-        Framebuffer {
+        let mut framebuffer = Framebuffer {
             width,
             height,
             buffer: vec![0; width * height],
+            hdr_buffer: vec![Color::black(); width * height],
             zbuffer: vec![f32::INFINITY; width * height],
-            background_color: 0x000000,
-            current_color: 0xFFFFFF,
+            raster_width: width,
+            raster_height: height,
+            sample_count: 1,
+            color_samples: Vec::new(),
+            depth_samples: Vec::new(),
+            overdraw: Vec::new(),
+            antialiasing: Antialiasing::None,
+            background_color: Color::black(),
+        };
+        framebuffer.set_antialiasing(Antialiasing::None);
+        framebuffer
+    }
+
+    pub fn set_antialiasing(&mut self, antialiasing: Antialiasing) {
+        let (scale, sample_count) = match antialiasing {
+            Antialiasing::None => (1, 1),
+            Antialiasing::Msaa(samples) => (1, samples.clamp(1, MAX_SAMPLES)),
+            Antialiasing::Ssaa(factor) => (factor.max(1), 1),
+        };
+
+        self.antialiasing = antialiasing;
+        self.raster_width = self.width * scale;
+        self.raster_height = self.height * scale;
+        self.sample_count = sample_count;
+
+        let len = self.raster_width * self.raster_height * sample_count;
+        self.color_samples = vec![self.background_color; len];
+        self.depth_samples = vec![f32::INFINITY; len];
+        self.overdraw = vec![0; self.raster_width * self.raster_height];
+    }
+
+    pub fn antialiasing(&self) -> Antialiasing {
+        self.antialiasing
+    }
+
+    pub fn sample_positions(&self) -> &'static [(i32, i32)] {
+        match self.sample_count {
+            2 => &SAMPLES_2,
+            4 => &SAMPLES_4,
+            8 => &SAMPLES_8,
+            _ => &SAMPLES_1,
         }
     }
 
     pub fn clear(&mut self) {
-        for pixel in self.buffer.iter_mut() {
+        for pixel in self.color_samples.iter_mut() {
             *pixel = self.background_color;
         }
-        for depth in self.zbuffer.iter_mut() {
+        for depth in self.depth_samples.iter_mut() {
             *depth = f32::INFINITY;
         }
+        self.overdraw.fill(0);
     }
 
-    pub fn point(&mut self, x: usize, y: usize, depth: f32) {
-        if x < self.width && y < self.height {
-            let index = y * self.width + x;
-            if depth < self.zbuffer[index] {
-                self.buffer[index] = self.current_color;
-                self.zbuffer[index] = depth;
-            }
-        }
+    // Promedia en espacio lineal las muestras de cada píxel hacia `hdr_buffer`
+    // y deja en `zbuffer` la profundidad más cercana
+    pub fn resolve(&mut self) {
+        let scale = self.raster_width / self.width;
+        let sample_count = self.sample_count;
+        let raster_width = self.raster_width;
+        let color_samples = &self.color_samples;
+        let depth_samples = &self.depth_samples;
+        let weight = 1.0 / (scale * scale * sample_count) as f32;
+
+        self.hdr_buffer
+            .par_chunks_mut(self.width)
+            .zip(self.zbuffer.par_chunks_mut(self.width))
+            .enumerate()
+            .for_each(|(y, (row, depth_row))| {
+                for (x, (pixel, depth)) in row.iter_mut().zip(depth_row.iter_mut()).enumerate() {
+                    let mut sum = Color::black();
+                    let mut nearest = f32::INFINITY;
+
+                    for sy in 0..scale {
+                        for sx in 0..scale {
+                            let raster_index = (y * scale + sy) * raster_width + x * scale + sx;
+                            let samples = raster_index * sample_count..(raster_index + 1) * sample_count;
+                            for &color in &color_samples[samples.clone()] {
+                                sum = sum + color.finite_or_black();
+                            }
+                            for &sample_depth in &depth_samples[samples] {
+                                nearest = nearest.min(sample_depth);
+                            }
+                        }
+                    }
+
+                    *pixel = sum * weight;
+                    *depth = nearest;
+                }
+            });
     }
 
-    pub fn set_background_color(&mut self, color: u32) {
-        self.background_color = color;
+    // Convierte `hdr_buffer` a píxeles de 8 bits en `buffer`
+    pub fn tone_map(&mut self, tone_mapping: &ToneMapping) {
+        self.buffer
+            .par_iter_mut()
+            .zip(self.hdr_buffer.par_iter())
+            .for_each(|(pixel, &color)| *pixel = tone_mapping.apply(color));
     }
 
-    pub fn set_current_color(&mut self, color: u32) {
-        self.current_color = color;
+    pub fn set_background_color(&mut self, color: u32) {
+        self.background_color = Color::from_hex(color);
     }
-}
\ No newline at end of file
+}
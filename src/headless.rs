@@ -0,0 +1,78 @@
+use std::io;
+use std::path::Path;
+use fastnoise_lite::FastNoiseLite;
+use crate::camera::Camera;
+use crate::cli::Options;
+use crate::debug::DebugMode;
+use crate::framebuffer::Framebuffer;
+use crate::image_io::save_buffer;
+use crate::postprocess::PostChain;
+use crate::scene::{render_scene, Scene};
+use crate::shaders::ShaderRegistry;
+use crate::tonemap::ToneMapping;
+use crate::{create_uniforms, BACKGROUND_COLOR, FRAME_TIME};
+
+// Renderiza sin ventana y guarda cada cuadro en disco
+pub fn run(
+    options: &Options,
+    shaders: &ShaderRegistry,
+    scene: &mut Scene,
+    camera: &Camera,
+    noise: &FastNoiseLite,
+    mut post_chain: PostChain,
+) -> io::Result<()> {
+    let mut framebuffer = Framebuffer::new(options.width, options.height);
+    framebuffer.set_background_color(BACKGROUND_COLOR);
+    framebuffer.set_antialiasing(options.antialiasing);
+
+    for frame in 0..options.frames {
+        let time = options.start_time + frame as f32 * FRAME_TIME;
+
+        framebuffer.clear();
+        scene.time = time * options.time_scale;
+        let mut uniforms = create_uniforms(camera, &options.lights, time, framebuffer.raster_width, framebuffer.raster_height);
+        let stats = render_scene(
+            &mut framebuffer,
+            scene,
+            &mut uniforms,
+            shaders,
+            noise,
+            options.raster_state,
+            options.debug_mode,
+        );
+        framebuffer.resolve();
+        if options.debug_mode == DebugMode::Off {
+            options.bloom.apply(&mut framebuffer);
+            post_chain.run(&mut framebuffer, time);
+            framebuffer.tone_map(&options.tone_mapping);
+        } else {
+            options.debug_mode.visualize(&mut framebuffer);
+            framebuffer.tone_map(&ToneMapping::default());
+        }
+
+        let path = frame_path(&options.output, frame, options.frames);
+        save_buffer(&path, framebuffer.width, framebuffer.height, &framebuffer.buffer)?;
+        println!(
+            "Wrote {} (time {:.3}s, {} triangles culled, {} fragments, {} shaded, {} rejected by early depth test)",
+            path, time, stats.culled_triangles, stats.fragments, stats.shaded, stats.early_z_rejected
+        );
+    }
+
+    Ok(())
+}
+
+// Con varios cuadros se numeran: frame.png -> frame_0000.png, frame_0001.png...
+pub fn frame_path(output: &str, frame: u32, frame_count: u32) -> String {
+    if frame_count == 1 {
+        return output.to_string();
+    }
+
+    let path = Path::new(output);
+    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("frame");
+    let numbered = match path.extension().and_then(|e| e.to_str()) {
+        Some(extension) => format!("{}_{:04}.{}", stem, frame, extension),
+        None => format!("{}_{:04}", stem, frame),
+    };
+
+    path.with_file_name(numbered).to_string_lossy().into_owned()
+}
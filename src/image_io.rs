@@ -0,0 +1,240 @@
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Write};
+use std::path::Path;
+
+// Imagen RGB de 8 bits leída de disco, filas de arriba hacia abajo
+pub struct RgbImage {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<[u8; 3]>,
+}
+
+fn extension(path: &str) -> Option<String> {
+    Path::new(path)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_ascii_lowercase())
+}
+
+fn invalid_data(path: &str, message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", path, message))
+}
+
+// Guarda un buffer 0xRRGGBB como PNG o PPM según la extensión del archivo
+pub fn save_buffer(path: &str, width: usize, height: usize, buffer: &[u32]) -> io::Result<()> {
+    match extension(path).as_deref() {
+        Some("png") => write_png(path, width, height, buffer),
+        Some("ppm") => write_ppm(path, width, height, buffer),
+        _ => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Unsupported image format: {}", path),
+        )),
+    }
+}
+
+pub fn write_ppm(path: &str, width: usize, height: usize, buffer: &[u32]) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    write!(writer, "P6\n{} {}\n255\n", width, height)?;
+    writer.write_all(&to_rgb_bytes(buffer))?;
+    writer.flush()
+}
+
+pub fn write_png(path: &str, width: usize, height: usize, buffer: &[u32]) -> io::Result<()> {
+    let writer = BufWriter::new(File::create(path)?);
+    let mut encoder = png::Encoder::new(writer, width as u32, height as u32);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+
+    let mut png_writer = encoder.write_header().map_err(io::Error::other)?;
+    png_writer
+        .write_image_data(&to_rgb_bytes(buffer))
+        .map_err(io::Error::other)
+}
+
+fn to_rgb_bytes(buffer: &[u32]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(buffer.len() * 3);
+    for pixel in buffer {
+        bytes.push((pixel >> 16) as u8);
+        bytes.push((pixel >> 8) as u8);
+        bytes.push(*pixel as u8);
+    }
+    bytes
+}
+
+// Carga PNG, PPM (P3/P6) o TGA (sin comprimir o RLE) según la extensión
+pub fn load_image(path: &str) -> io::Result<RgbImage> {
+    match extension(path).as_deref() {
+        Some("png") => read_png(path),
+        Some("ppm") => read_ppm(path),
+        Some("tga") => read_tga(path),
+        _ => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Unsupported image format: {}", path),
+        )),
+    }
+}
+
+fn read_png(path: &str) -> io::Result<RgbImage> {
+    let mut decoder = png::Decoder::new(BufReader::new(File::open(path)?));
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+
+    let mut reader = decoder.read_info().map_err(io::Error::other)?;
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buffer).map_err(io::Error::other)?;
+    let bytes = &buffer[..info.buffer_size()];
+
+    let pixels = match info.color_type {
+        png::ColorType::Rgb => bytes.chunks_exact(3).map(|p| [p[0], p[1], p[2]]).collect(),
+        png::ColorType::Rgba => bytes.chunks_exact(4).map(|p| [p[0], p[1], p[2]]).collect(),
+        png::ColorType::Grayscale => bytes.iter().map(|&v| [v, v, v]).collect(),
+        png::ColorType::GrayscaleAlpha => bytes.chunks_exact(2).map(|p| [p[0], p[0], p[0]]).collect(),
+        png::ColorType::Indexed => return Err(invalid_data(path, "unexpanded palette")),
+    };
+
+    Ok(RgbImage { width: info.width as usize, height: info.height as usize, pixels })
+}
+
+fn read_ppm(path: &str) -> io::Result<RgbImage> {
+    let data = fs::read(path)?;
+
+    // Encabezado: número mágico, ancho, alto y valor máximo, con comentarios '#'
+    let mut position = 0;
+    let magic = ppm_token(&data, &mut position).ok_or_else(|| invalid_data(path, "empty file"))?;
+    let number = |position: &mut usize| {
+        ppm_token(&data, position)
+            .and_then(|token| token.parse::<usize>().ok())
+            .ok_or_else(|| invalid_data(path, "invalid header"))
+    };
+    let width = number(&mut position)?;
+    let height = number(&mut position)?;
+    let max_value = number(&mut position)?;
+    if max_value == 0 || max_value > 255 {
+        return Err(invalid_data(path, "only 8-bit PPM files are supported"));
+    }
+    let scale = |value: usize| (value.min(max_value) * 255 / max_value) as u8;
+    let sample_count = width
+        .checked_mul(height)
+        .and_then(|count| count.checked_mul(3))
+        .ok_or_else(|| invalid_data(path, "image dimensions too large"))?;
+
+    let samples: Vec<u8> = match magic {
+        "P6" => {
+            // Un solo byte de espacio separa el encabezado de los datos binarios
+            let start = position + 1;
+            let bytes = start
+                .checked_add(sample_count)
+                .and_then(|end| data.get(start..end))
+                .ok_or_else(|| invalid_data(path, "truncated pixel data"))?;
+            bytes.iter().map(|&value| scale(value as usize)).collect()
+        }
+        "P3" => (0..sample_count)
+            .map(|_| number(&mut position).map(scale))
+            .collect::<io::Result<_>>()?,
+        _ => return Err(invalid_data(path, "not a P3/P6 PPM file")),
+    };
+
+    let pixels = samples.chunks_exact(3).map(|p| [p[0], p[1], p[2]]).collect();
+    Ok(RgbImage { width, height, pixels })
+}
+
+// Siguiente palabra del encabezado PPM, saltando espacios y comentarios
+fn ppm_token<'a>(data: &'a [u8], position: &mut usize) -> Option<&'a str> {
+    loop {
+        while data.get(*position)?.is_ascii_whitespace() {
+            *position += 1;
+        }
+        if data[*position] != b'#' {
+            break;
+        }
+        while *data.get(*position)? != b'\n' {
+            *position += 1;
+        }
+    }
+
+    let start = *position;
+    while data.get(*position).is_some_and(|c| !c.is_ascii_whitespace()) {
+        *position += 1;
+    }
+    std::str::from_utf8(&data[start..*position]).ok()
+}
+
+fn read_tga(path: &str) -> io::Result<RgbImage> {
+    let data = fs::read(path)?;
+    let header = data.get(..18).ok_or_else(|| invalid_data(path, "truncated header"))?;
+
+    let id_length = header[0] as usize;
+    let image_type = header[2];
+    let width = u16::from_le_bytes([header[12], header[13]]) as usize;
+    let height = u16::from_le_bytes([header[14], header[15]]) as usize;
+    let bytes_per_pixel = header[16] as usize / 8;
+    let top_to_bottom = header[17] & 0x20 != 0;
+    // Con ancho 0 las filas no se pueden recorrer más abajo
+    if width == 0 || height == 0 {
+        return Err(invalid_data(path, "empty image"));
+    }
+
+    if header[1] != 0 {
+        return Err(invalid_data(path, "color-mapped TGA files are not supported"));
+    }
+    let (compressed, expected_bytes) = match image_type {
+        2 => (false, [3, 4]),
+        3 => (false, [1, 1]),
+        10 => (true, [3, 4]),
+        11 => (true, [1, 1]),
+        _ => return Err(invalid_data(path, "unsupported TGA image type")),
+    };
+    if !expected_bytes.contains(&bytes_per_pixel) {
+        return Err(invalid_data(path, "unsupported TGA pixel depth"));
+    }
+
+    let body = data.get(18 + id_length..).ok_or_else(|| invalid_data(path, "truncated header"))?;
+    let mut cursor = 0;
+    let mut take = |count: usize| -> io::Result<&[u8]> {
+        let bytes = body
+            .get(cursor..cursor + count)
+            .ok_or_else(|| invalid_data(path, "truncated pixel data"))?;
+        cursor += count;
+        Ok(bytes)
+    };
+    // Los píxeles se guardan como BGR(A) o gris
+    let decode = |p: &[u8]| if bytes_per_pixel == 1 { [p[0], p[0], p[0]] } else { [p[2], p[1], p[0]] };
+
+    let count = width
+        .checked_mul(height)
+        .ok_or_else(|| invalid_data(path, "image dimensions too large"))?;
+    // Sin compresión el tamaño del cuerpo se conoce de antemano; con RLE cada
+    // paquete ocupa al menos un byte, así que la reserva se limita a la entrada
+    // para que un encabezado falso no reserve gigabytes
+    if !compressed && count.checked_mul(bytes_per_pixel).is_none_or(|size| size > body.len()) {
+        return Err(invalid_data(path, "truncated pixel data"));
+    }
+    let mut pixels = Vec::with_capacity(count.min(body.len()));
+    while pixels.len() < count {
+        if !compressed {
+            pixels.push(decode(take(bytes_per_pixel)?));
+            continue;
+        }
+
+        // Paquete RLE: un byte con el bit alto para repetir y la cantidad - 1 en
+        // los 7 bits bajos, seguido de un píxel repetido o de la lista literal
+        let packet = take(1)?[0];
+        let length = (packet & 0x7f) as usize + 1;
+        if packet & 0x80 != 0 {
+            let pixel = decode(take(bytes_per_pixel)?);
+            pixels.extend(std::iter::repeat_n(pixel, length));
+        } else {
+            for _ in 0..length {
+                pixels.push(decode(take(bytes_per_pixel)?));
+            }
+        }
+    }
+    pixels.truncate(count);
+
+    // El origen por defecto de TGA es la esquina inferior izquierda
+    if !top_to_bottom {
+        pixels = pixels.chunks(width).rev().flatten().copied().collect();
+    }
+
+    Ok(RgbImage { width, height, pixels })
+}
+
@@ -0,0 +1,173 @@
+use std::str::FromStr;
+use nalgebra_glm::Vec3;
+use crate::color::Color;
+
+#[derive(Debug, Clone, Copy)]
+pub enum LightKind {
+    // `direction` es hacia donde viaja la luz
+    Directional { direction: Vec3 },
+    // La intensidad cae a la mitad a una distancia `range`
+    Point { position: Vec3, range: f32 },
+    // Conos en coseno: luz completa dentro de `inner_cos`, nada fuera de `outer_cos`
+    Spot { position: Vec3, direction: Vec3, range: f32, inner_cos: f32, outer_cos: f32 },
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Light {
+    pub kind: LightKind,
+    pub color: Color,
+    pub intensity: f32,
+}
+
+// Propiedades de superficie para Blinn-Phong
+#[derive(Debug, Clone, Copy)]
+pub struct Material {
+    pub albedo: Color,
+    pub ambient: f32,
+    pub specular: f32,
+    pub shininess: f32,
+}
+
+// Luz desde la cámara inicial, como la iluminación original
+impl Default for Light {
+    fn default() -> Self {
+        Light::directional(Vec3::new(0.0, 0.0, -1.0))
+    }
+}
+
+impl Light {
+    pub fn directional(direction: Vec3) -> Self {
+        Light {
+            kind: LightKind::Directional { direction: direction.normalize() },
+            color: Color::from_float(1.0, 1.0, 1.0),
+            intensity: 1.0,
+        }
+    }
+
+    pub fn point(position: Vec3, range: f32) -> Self {
+        Light {
+            kind: LightKind::Point { position, range },
+            color: Color::from_float(1.0, 1.0, 1.0),
+            intensity: 1.0,
+        }
+    }
+
+    pub fn spot(position: Vec3, direction: Vec3, range: f32, inner_degrees: f32, outer_degrees: f32) -> Self {
+        Light {
+            kind: LightKind::Spot {
+                position,
+                direction: direction.normalize(),
+                range,
+                inner_cos: inner_degrees.to_radians().cos(),
+                outer_cos: outer_degrees.to_radians().cos(),
+            },
+            color: Color::from_float(1.0, 1.0, 1.0),
+            intensity: 1.0,
+        }
+    }
+
+    // Dirección normalizada hacia la luz y radiancia que llega a `point`
+    pub fn incident(&self, point: &Vec3) -> (Vec3, Color) {
+        let radiance = self.color * self.intensity;
+
+        match self.kind {
+            LightKind::Directional { direction } => (-direction, radiance),
+            LightKind::Point { position, range } => {
+                let (to_light, attenuation) = attenuate(position - point, range);
+                (to_light, radiance * attenuation)
+            }
+            LightKind::Spot { position, direction, range, inner_cos, outer_cos } => {
+                let (to_light, attenuation) = attenuate(position - point, range);
+                let cos_angle = (-to_light).dot(&direction);
+                let t = ((cos_angle - outer_cos) / (inner_cos - outer_cos).max(1e-4)).clamp(0.0, 1.0);
+                let cone = t * t * (3.0 - 2.0 * t);
+                (to_light, radiance * (attenuation * cone))
+            }
+        }
+    }
+}
+
+fn attenuate(offset: Vec3, range: f32) -> (Vec3, f32) {
+    let distance = offset.magnitude().max(1e-4);
+    let ratio = distance / range.max(1e-4);
+    (offset / distance, 1.0 / (1.0 + ratio * ratio))
+}
+
+// Difuso de Lambert con todas las luces; `position` y `normal` en espacio de mundo
+pub fn lambert(lights: &[Light], position: &Vec3, normal: &Vec3, albedo: Color) -> Color {
+    let normal = normal.normalize();
+    lights.iter().fold(Color::black(), |sum, light| {
+        let (to_light, radiance) = light.incident(position);
+        sum + albedo * radiance * normal.dot(&to_light).max(0.0)
+    })
+}
+
+// Lambert más el reflejo especular de Blinn-Phong visto desde `eye`
+pub fn blinn_phong(lights: &[Light], position: &Vec3, normal: &Vec3, eye: &Vec3, material: &Material) -> Color {
+    let normal = normal.normalize();
+    let to_eye = (eye - position).normalize();
+    let ambient = material.albedo * material.ambient;
+
+    lights.iter().fold(ambient, |sum, light| {
+        let (to_light, radiance) = light.incident(position);
+        let n_dot_l = normal.dot(&to_light);
+        if n_dot_l <= 0.0 {
+            return sum;
+        }
+
+        let half = (to_light + to_eye).normalize();
+        let specular = normal.dot(&half).max(0.0).powf(material.shininess) * material.specular;
+        sum + (material.albedo * n_dot_l + Color::from_float(specular, specular, specular)) * radiance
+    })
+}
+
+// kind:x,y,z[:dx,dy,dz][:clave=valor...], por ejemplo
+// `point:0,10,0:range=50:color=ffddaa:intensity=2` o `spot:0,0,30:0,0,-1:angle=25`
+impl FromStr for Light {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid light: {} (use directional:DX,DY,DZ, point:X,Y,Z or spot:X,Y,Z:DX,DY,DZ)", s);
+        let mut parts = s.split(':');
+        let kind = parts.next().ok_or_else(invalid)?;
+        let mut vector = || parse_vec3(parts.next().ok_or_else(invalid)?).ok_or_else(invalid);
+
+        let mut light = match kind {
+            "directional" => Light::directional(vector()?),
+            "point" => Light::point(vector()?, 50.0),
+            "spot" => {
+                let position = vector()?;
+                Light::spot(position, vector()?, 50.0, 20.0, 30.0)
+            }
+            _ => return Err(invalid()),
+        };
+
+        for option in parts {
+            let (key, value) = option.split_once('=').ok_or_else(invalid)?;
+            let number = || value.parse::<f32>().map_err(|_| invalid());
+
+            match (key, &mut light.kind) {
+                ("color", _) => {
+                    let hex = u32::from_str_radix(value, 16).map_err(|_| invalid())?;
+                    light.color = Color::from_hex(hex);
+                }
+                ("intensity", _) => light.intensity = number()?,
+                ("range", LightKind::Point { range, .. } | LightKind::Spot { range, .. }) => *range = number()?,
+                ("angle", LightKind::Spot { inner_cos, outer_cos, .. }) => {
+                    let outer = number()?;
+                    *outer_cos = outer.to_radians().cos();
+                    *inner_cos = (outer * 0.75).to_radians().cos();
+                }
+                _ => return Err(invalid()),
+            }
+        }
+
+        Ok(light)
+    }
+}
+
+pub fn parse_vec3(text: &str) -> Option<Vec3> {
+    let mut values = text.split(',').map(|value| value.trim().parse::<f32>());
+    let vector = Vec3::new(values.next()?.ok()?, values.next()?.ok()?, values.next()?.ok()?);
+    values.next().is_none().then_some(vector)
+}
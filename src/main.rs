@@ -1,5 +1,5 @@
 use nalgebra_glm::{Vec3, Mat4, look_at, perspective};
-use minifb::{Key, Window, WindowOptions};
+use minifb::{Key, KeyRepeat, Window, WindowOptions};
 use std::time::{Duration, Instant};
 use std::f32::consts::PI;
 
@@ -10,14 +10,42 @@ mod obj;
 mod color;
 mod fragment;
 mod shaders;
+mod celestial;
 mod camera;
+mod bloom;
+mod tonemap;
+mod culling;
+mod clipping;
+mod tiles;
+mod cli;
+mod headless;
+mod image_io;
+mod postprocess;
+mod debug;
+mod light;
+mod texture;
+mod bake;
+mod scene;
+mod orbit;
+mod solar_system;
+mod mesh;
 
 use framebuffer::Framebuffer;
 use vertex::Vertex;
-use obj::Obj;
 use camera::Camera;
-use triangle::triangle;
-use shaders::{vertex_shader, fragment_shader};
+use mesh::MeshSource;
+use tiles::{render_tiles, RenderStats};
+use clipping::{clip_triangle, project};
+use culling::{CullMode, FrontFace, RasterState};
+use shaders::{MaterialShader, Shader, ShaderRegistry, TexturedShader};
+use cli::{Options, USAGE};
+use postprocess::{PostChain, DEFAULT_CONFIG};
+use debug::DebugMode;
+use scene::{render_scene, MeshPart, Scene};
+use tonemap::ToneMapping;
+use light::{Light, Material};
+use texture::{Sampler, Texture};
+use color::Color;
 use fastnoise_lite::{FastNoiseLite, NoiseType, FractalType};
 
 pub struct Uniforms {
@@ -26,6 +54,8 @@ pub struct Uniforms {
     projection_matrix: Mat4,
     viewport_matrix: Mat4,
     time: f32,
+    camera_position: Vec3,
+    lights: Vec<Light>,
 }
 
 fn create_noise() -> FastNoiseLite {
@@ -39,6 +69,14 @@ fn create_noise() -> FastNoiseLite {
     noise
 }
 
+pub const NEAR_PLANE: f32 = 0.1;
+pub const FAR_PLANE: f32 = 1000.0;
+
+// Profundidad NDC [-1, 1] -> distancia a la cámara en unidades de mundo
+pub fn linearize_depth(ndc_z: f32) -> f32 {
+    2.0 * NEAR_PLANE * FAR_PLANE / (FAR_PLANE + NEAR_PLANE - ndc_z * (FAR_PLANE - NEAR_PLANE))
+}
+
 fn create_model_matrix(translation: Vec3, scale: f32, rotation: Vec3) -> Mat4 {
     let (sin_x, cos_x) = rotation.x.sin_cos();
     let (sin_y, cos_y) = rotation.y.sin_cos();
@@ -84,10 +122,7 @@ fn create_view_matrix(eye: Vec3, center: Vec3, up: Vec3) -> Mat4 {
 fn create_perspective_matrix(window_width: f32, window_height: f32) -> Mat4 {
     let fov = 45.0 * PI / 180.0;
     let aspect_ratio = window_width / window_height;
-    let near = 0.1;
-    let far = 1000.0;
-
-    perspective(fov, aspect_ratio, near, far)
+    perspective(fov, aspect_ratio, NEAR_PLANE, FAR_PLANE)
 }
 
 fn create_viewport_matrix(width: f32, height: f32) -> Mat4 {
@@ -99,41 +134,237 @@ fn create_viewport_matrix(width: f32, height: f32) -> Mat4 {
     )
 }
 
-fn render(framebuffer: &mut Framebuffer, uniforms: &Uniforms, vertex_array: &[Vertex], noise: &FastNoiseLite) {
+fn render<S: Shader + ?Sized>(
+    framebuffer: &mut Framebuffer,
+    uniforms: &Uniforms,
+    vertex_array: &[Vertex],
+    shader: &S,
+    noise: &FastNoiseLite,
+    raster_state: RasterState,
+) -> RenderStats {
     // Transformar vértices
     let transformed_vertices: Vec<Vertex> = vertex_array
         .iter()
-        .map(|v| vertex_shader(v, uniforms, noise))
+        .map(|v| shader.vertex_shader(v, uniforms, noise))
         .collect();
 
-    // Rasterizar triángulos
-    for i in (0..transformed_vertices.len()).step_by(3) {
-        if i + 2 < transformed_vertices.len() {
-            let fragments = triangle(
-                &transformed_vertices[i],
-                &transformed_vertices[i + 1],
-                &transformed_vertices[i + 2]
-            );
-
-            // Sombrear fragmentos
-            for fragment in fragments {
-                let x = fragment.position.x as usize;
-                let y = fragment.position.y as usize;
-                if x < framebuffer.width && y < framebuffer.height {
-                    let shaded_color = fragment_shader(&fragment, &uniforms, noise);
-                    framebuffer.set_current_color(shaded_color.to_hex());
-                    framebuffer.point(x, y, fragment.depth);
+    // Recortar en espacio de recorte, descartar caras según el modo de
+    // culling y proyectar a pantalla
+    let double_sided = shader.double_sided();
+    // Las mallas generadas y cargadas siempre emiten triángulos CCW, así que la
+    // cara trasera se decide con ese orden sin importar --front-face
+    let mesh_winding = RasterState { cull_mode: CullMode::None, front_face: FrontFace::CounterClockwise };
+    let raster_state = if double_sided {
+        RasterState { cull_mode: CullMode::None, ..raster_state }
+    } else {
+        raster_state
+    };
+    let mut screen_vertices = Vec::with_capacity(transformed_vertices.len());
+    let mut clipped = Vec::new();
+    let mut culled_triangles = 0;
+    for tri in transformed_vertices.chunks_exact(3) {
+        clipped.clear();
+        clip_triangle(&tri[0], &tri[1], &tri[2], &mut clipped);
+
+        for piece in clipped.chunks_exact(3) {
+            if raster_state.is_culled(&piece[0], &piece[1], &piece[2]) {
+                culled_triangles += 1;
+                continue;
+            }
+            // En las caras traseras de una superficie de doble cara la normal mira hacia la cámara
+            let back_facing = double_sided && !mesh_winding.is_front_facing(&piece[0], &piece[1], &piece[2]);
+            for vertex in piece {
+                let mut vertex = vertex.clone();
+                if back_facing {
+                    vertex.transformed_normal = -vertex.transformed_normal;
                 }
+                project(&mut vertex, &uniforms.viewport_matrix);
+                screen_vertices.push(vertex);
+            }
+        }
+    }
+
+    // Rasterizar y sombrear por tiles en paralelo, con prueba de profundidad
+    // temprana salvo que el shader modifique la profundidad
+    let early_depth_test = !shader.modifies_depth();
+    let transparent = shader.is_transparent();
+    let mut stats = render_tiles(framebuffer, &screen_vertices, early_depth_test, transparent, |fragment| {
+        let color = shader.fragment_shader(fragment, uniforms, noise);
+        let alpha = if transparent { shader.fragment_alpha(fragment, uniforms, noise) } else { 1.0 };
+        let depth = (!early_depth_test).then(|| shader.fragment_depth(fragment, uniforms, noise));
+        (color, alpha, depth)
+    });
+
+    stats.culled_triangles = culled_triangles;
+    stats
+}
+
+pub const FRAME_TIME: f32 = 0.016;
+const ROTATION_SPEED: f32 = 0.12;
+const BACKGROUND_COLOR: u32 = 0x000008;
+
+// Cada material del MTL se registra como el shader "mtl:NOMBRE" de su submalla
+fn load_mesh(source: &MeshSource, sampler: Sampler, shaders: &mut ShaderRegistry) -> Vec<MeshPart> {
+    println!("Loading mesh {:?}...", source);
+    let submeshes = match source.load() {
+        Ok((submeshes, warnings)) => {
+            for warning in warnings {
+                eprintln!("Warning: {}", warning);
             }
+            submeshes
+        }
+        Err(message) => {
+            eprintln!("Failed to load mesh: {}", message);
+            std::process::exit(2);
         }
+    };
+
+    let mut parts = Vec::with_capacity(submeshes.len());
+    for submesh in submeshes {
+        let shader = submesh.material.map(|material| {
+            let name = format!("mtl:{}", material.name);
+            match MaterialShader::from_mtl(&material, sampler) {
+                Ok(shader) => shaders.register(&name, shader),
+                Err(err) => {
+                    let path = material.diffuse_map.as_deref().unwrap_or_default();
+                    eprintln!("Failed to load texture {} for material '{}': {}", path, material.name, err);
+                    std::process::exit(2);
+                }
+            }
+            name
+        });
+        parts.push(MeshPart { vertices: submesh.vertices, shader });
+    }
+
+    let vertex_count: usize = parts.iter().map(|part| part.vertices.len()).sum();
+    println!("Loaded {} vertices ({} triangles)", vertex_count, vertex_count / 3);
+    let materials = parts.iter().filter(|part| part.shader.is_some()).count();
+    if materials > 0 {
+        println!("{} submeshes, {} with MTL materials", parts.len(), materials);
+    }
+    parts
+}
+
+fn create_camera() -> Camera {
+    Camera::new(
+        Vec3::new(0.0, 0.0, 85.0),
+        Vec3::new(0.0, 0.0, 0.0),
+        Vec3::new(0.0, 1.0, 0.0)
+    )
+}
+
+// La matriz de modelo la asigna `render_scene` para cada nodo
+fn create_uniforms(camera: &Camera, lights: &[Light], time: f32, width: usize, height: usize) -> Uniforms {
+    Uniforms {
+        model_matrix: Mat4::identity(),
+        view_matrix: create_view_matrix(camera.eye, camera.center, camera.up),
+        projection_matrix: create_perspective_matrix(width as f32, height as f32),
+        viewport_matrix: create_viewport_matrix(width as f32, height as f32),
+        time,
+        camera_position: camera.eye,
+        lights: lights.to_vec(),
     }
 }
 
 fn main() {
-    let window_width = 800;
-    let window_height = 600;
-    let framebuffer_width = 800;
-    let framebuffer_height = 600;
+    let options = match Options::parse(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("{}\n{}", message, USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    let mut shaders = ShaderRegistry::with_defaults();
+    // Con --texture se agrega el shader "textured"
+    if let Some(path) = &options.texture {
+        let texture = match Texture::load(path) {
+            Ok(texture) => texture,
+            Err(err) => {
+                eprintln!("Failed to load texture: {}", err);
+                std::process::exit(2);
+            }
+        };
+        println!("Loaded texture {} ({}x{}, {} mip levels)", path, texture.width, texture.height, texture.mip_levels());
+        shaders.register("textured", TexturedShader {
+            texture,
+            sampler: options.sampler,
+            material: Material { albedo: Color::from_float(1.0, 1.0, 1.0), ambient: 0.1, specular: 0.15, shininess: 24.0 },
+            noise_detail: 0.15,
+        });
+    }
+    if shaders.get(&options.shader).is_none() {
+        eprintln!("Unknown shader '{}'. Available: {}", options.shader, shaders.names().join(", "));
+        std::process::exit(2);
+    }
+
+    // Sin el archivo por defecto simplemente no hay post-proceso
+    let post_chain = if options.post_config == DEFAULT_CONFIG && !std::path::Path::new(DEFAULT_CONFIG).exists() {
+        PostChain::default()
+    } else {
+        match PostChain::load(&options.post_config) {
+            Ok(chain) => chain,
+            Err(message) => {
+                eprintln!("Failed to load post-processing chain: {}", message);
+                std::process::exit(2);
+            }
+        }
+    };
+
+    let noise = create_noise();
+
+    // El horneado no necesita la malla
+    if options.bake {
+        if let Err(err) = bake::run(&options, &shaders, &noise) {
+            eprintln!("Bake failed: {}", err);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    let mesh = load_mesh(&options.mesh, options.sampler, &mut shaders);
+
+    // Con --system los cuerpos y la cámara salen del archivo
+    let (mut scene, camera) = match &options.system {
+        Some(path) => match solar_system::load(path, mesh, &shaders) {
+            Ok(system) => {
+                println!("Loaded solar system {} ({} nodes)", path, system.scene.nodes.len());
+                (system.scene, system.camera)
+            }
+            Err(message) => {
+                eprintln!("Failed to load solar system: {}", message);
+                std::process::exit(2);
+            }
+        },
+        None => (
+            Scene::single_body(mesh, &options.shader, 12.0, Vec3::new(0.0, ROTATION_SPEED, 0.0)),
+            create_camera(),
+        ),
+    };
+
+    if options.headless {
+        if let Err(err) = headless::run(&options, &shaders, &mut scene, &camera, &noise, post_chain) {
+            eprintln!("Headless render failed: {}", err);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    run_window(&options, &shaders, &mut scene, camera, &noise, post_chain);
+}
+
+fn run_window(
+    options: &Options,
+    shaders: &ShaderRegistry,
+    scene: &mut Scene,
+    mut camera: Camera,
+    noise: &FastNoiseLite,
+    mut post_chain: PostChain,
+) {
+    let window_width = options.width;
+    let window_height = options.height;
+    let framebuffer_width = options.width;
+    let framebuffer_height = options.height;
     let frame_delay = Duration::from_millis(16);
 
     println!("Initializing framebuffer...");
@@ -150,43 +381,35 @@ fn main() {
     window.set_position(500, 500);
     window.update();
 
-    framebuffer.set_background_color(0x000008);
-
-    println!("Loading sphere model...");
-    let obj_path = if std::path::Path::new("assets/sphere_smooth.obj").exists() {
-        println!("Using smooth sphere (320 triangles)");
-        "assets/sphere_smooth.obj"
-    } else if std::path::Path::new("assets/sphere_medium.obj").exists() {
-        println!("Using medium sphere (80 triangles)");
-        "assets/sphere_medium.obj"
-    } else if std::path::Path::new("assets/sphere_simple.obj").exists() {
-        println!("Using simple sphere (20 triangles)");
-        "assets/sphere_simple.obj"
-    } else {
-        println!("Using original sphere");
-        "assets/sphere.obj"
-    };
-    
-    let obj = Obj::load(obj_path).expect("Failed to load obj");
-    let vertex_arrays = obj.get_vertex_array();
-    let triangle_count = vertex_arrays.len() / 3;
-    println!("Loaded {} vertices ({} triangles)", vertex_arrays.len(), triangle_count);
-
-    let mut camera = Camera::new(
-        Vec3::new(0.0, 0.0, 85.0),
-        Vec3::new(0.0, 0.0, 0.0),
-        Vec3::new(0.0, 1.0, 0.0)
-    );
+    framebuffer.set_background_color(BACKGROUND_COLOR);
+    framebuffer.set_antialiasing(options.antialiasing);
 
-    let noise = create_noise();
+    let mut tab_was_down = false;
     
-    let mut time = 0.0;
-    let rotation_speed = 0.12;
+    let mut time = options.start_time;
+    // Las órbitas avanzan con su propio reloj para poder cambiar la escala sin saltos
+    let mut time_scale = options.time_scale;
+    scene.time = options.start_time * time_scale;
     let mut frame_count = 0;
     let mut last_fps_print = Instant::now();
     let mut fps_counter = 0;
-
-    const MIN_DISTANCE: f32 = 85.0;
+    let mut stats = RenderStats::default();
+    let mut raster_state = options.raster_state;
+    let mut cull_was_down = false;
+    let mut aa_was_down = false;
+    let mut tone_mapping = options.tone_mapping;
+    let mut tone_was_down = false;
+    let mut bloom = options.bloom;
+    let mut bloom_was_down = false;
+    const EFFECT_KEYS: [Key; 9] = [
+        Key::Key1, Key::Key2, Key::Key3, Key::Key4, Key::Key5,
+        Key::Key6, Key::Key7, Key::Key8, Key::Key9,
+    ];
+    let mut effect_was_down = [false; 9];
+    let mut debug_mode = options.debug_mode;
+    const DEBUG_KEYS: [Key; 8] = [Key::F1, Key::F2, Key::F3, Key::F4, Key::F5, Key::F6, Key::F7, Key::F8];
+
+    const MIN_DISTANCE: f32 = 2.0;
     const MAX_DISTANCE: f32 = 200.0;
 
     println!("\nStarting render loop...");
@@ -195,6 +418,19 @@ fn main() {
     println!("  S: Zoom out (maximum distance: {:.0})", MAX_DISTANCE);
     println!("  A/D: Orbit left/right");
     println!("  Q/E: Orbit up/down");
+    println!("  TAB: Cycle shader ({})", shaders.names().join(", "));
+    println!("  C: Cycle cull mode (none/back/front)");
+    println!("  M: Cycle antialiasing (none/MSAA 4x/SSAA 2x2)");
+    println!("  T: Cycle tone mapping (clamp/reinhard/aces)");
+    println!("  +/-: Exposure");
+    println!("  [/]: Slow down/speed up scene time");
+    println!("  B: Toggle bloom");
+    for (index, entry) in post_chain.effects.iter().take(EFFECT_KEYS.len()).enumerate() {
+        println!("  {}: Toggle {} ({})", index + 1, entry.effect.name(), if entry.enabled { "on" } else { "off" });
+    }
+    for (key, mode) in DebugMode::ALL.iter().enumerate() {
+        println!("  F{}: Debug view {}", key + 1, mode.name());
+    }
     println!("  ESC: Exit\n");
 
     while window.is_open() {
@@ -204,7 +440,8 @@ fn main() {
             break;
         }
 
-        time += 0.016;
+        time += FRAME_TIME;
+        scene.time += FRAME_TIME * time_scale;
         frame_count += 1;
         fps_counter += 1;
 
@@ -212,14 +449,84 @@ fn main() {
             let fps = fps_counter as f32 / last_fps_print.elapsed().as_secs_f32();
             let distance = (camera.eye - camera.center).magnitude();
             
-            println!("FPS: {:.1} | Frame: {} | Time: {:.1}s | Distance: {:.1}", 
-                     fps, frame_count, time, distance);
+            println!("FPS: {:.1} | Frame: {} | Time: {:.1}s | Distance: {:.1} | Culled: {} | Early-Z rejected: {}/{}", 
+                     fps, frame_count, time, distance, stats.culled_triangles, stats.early_z_rejected, stats.fragments);
             fps_counter = 0;
             last_fps_print = Instant::now();
         }
 
         handle_input(&window, &mut camera);
 
+        let tab_down = window.is_key_down(Key::Tab);
+        if tab_down
+            && !tab_was_down
+            && let Some(body) = scene.first_body()
+            && let Some(next) = shaders.next_name(&scene.nodes[body].shader)
+        {
+            scene.nodes[body].shader = next.to_string();
+            println!("Shader ({}): {}", scene.nodes[body].name, next);
+        }
+        tab_was_down = tab_down;
+
+        let cull_down = window.is_key_down(Key::C);
+        if cull_down && !cull_was_down {
+            raster_state.cull_mode = raster_state.cull_mode.next();
+            println!("Cull mode: {:?}", raster_state.cull_mode);
+        }
+        cull_was_down = cull_down;
+
+        let aa_down = window.is_key_down(Key::M);
+        if aa_down && !aa_was_down {
+            framebuffer.set_antialiasing(framebuffer.antialiasing().next());
+            println!("Antialiasing: {:?}", framebuffer.antialiasing());
+        }
+        aa_was_down = aa_down;
+
+        let tone_down = window.is_key_down(Key::T);
+        if tone_down && !tone_was_down {
+            tone_mapping.operator = tone_mapping.operator.next();
+            println!("Tone mapping: {:?}", tone_mapping.operator);
+        }
+        tone_was_down = tone_down;
+
+        let bloom_down = window.is_key_down(Key::B);
+        if bloom_down && !bloom_was_down {
+            bloom.enabled = !bloom.enabled;
+            println!("Bloom: {}", if bloom.enabled { "on" } else { "off" });
+        }
+        bloom_was_down = bloom_down;
+
+        for (index, key) in EFFECT_KEYS.iter().enumerate() {
+            let down = window.is_key_down(*key);
+            if down && !effect_was_down[index] && let Some((name, enabled)) = post_chain.toggle(index) {
+                println!("{}: {}", name, if enabled { "on" } else { "off" });
+            }
+            effect_was_down[index] = down;
+        }
+
+        for (key, mode) in DEBUG_KEYS.iter().zip(DebugMode::ALL) {
+            if window.is_key_pressed(*key, KeyRepeat::No) && debug_mode != mode {
+                debug_mode = mode;
+                println!("Debug view: {}", debug_mode.name());
+            }
+        }
+
+        if window.is_key_pressed(Key::LeftBracket, KeyRepeat::No) {
+            time_scale /= 2.0;
+            println!("Time scale: {}", time_scale);
+        }
+        if window.is_key_pressed(Key::RightBracket, KeyRepeat::No) {
+            time_scale *= 2.0;
+            println!("Time scale: {}", time_scale);
+        }
+
+        if window.is_key_down(Key::Equal) {
+            tone_mapping.exposure *= 1.02;
+        }
+        if window.is_key_down(Key::Minus) {
+            tone_mapping.exposure /= 1.02;
+        }
+
         let distance = (camera.eye - camera.center).magnitude();
         if distance < MIN_DISTANCE {
             let direction = (camera.eye - camera.center).normalize();
@@ -231,17 +538,18 @@ fn main() {
 
         framebuffer.clear();
 
-        let rotation = Vec3::new(0.0, time * rotation_speed, 0.0);
-
-        let uniforms = Uniforms {
-            model_matrix: create_model_matrix(Vec3::new(0.0, 0.0, 0.0), 12.0, rotation),
-            view_matrix: create_view_matrix(camera.eye, camera.center, camera.up),
-            projection_matrix: create_perspective_matrix(window_width as f32, window_height as f32),
-            viewport_matrix: create_viewport_matrix(framebuffer_width as f32, framebuffer_height as f32),
-            time,
-        };
-
-        render(&mut framebuffer, &uniforms, &vertex_arrays, &noise);
+        let mut uniforms = create_uniforms(&camera, &options.lights, time, framebuffer.raster_width, framebuffer.raster_height);
+        stats = render_scene(&mut framebuffer, scene, &mut uniforms, shaders, noise, raster_state, debug_mode);
+        framebuffer.resolve();
+        if debug_mode == DebugMode::Off {
+            bloom.apply(&mut framebuffer);
+            post_chain.run(&mut framebuffer, time);
+            framebuffer.tone_map(&tone_mapping);
+        } else {
+            // Los valores de depuración se muestran sin efectos ni tone mapping
+            debug_mode.visualize(&mut framebuffer);
+            framebuffer.tone_map(&ToneMapping::default());
+        }
 
         window
             .update_with_buffer(&framebuffer.buffer, framebuffer_width, framebuffer_height)
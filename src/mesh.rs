@@ -0,0 +1,310 @@
+use std::f32::consts::PI;
+use std::str::FromStr;
+use nalgebra_glm::{Vec2, Vec3};
+use crate::obj::{Obj, Submesh};
+use crate::vertex::Vertex;
+
+// Generadores de mallas procedurales. Devuelven listas de triángulos sin
+// índices, igual que las submallas de `Obj`, en sentido antihorario visto
+// desde el lado hacia donde apunta la normal.
+//
+// Las esferas tienen radio 1 y usan el mismo mapeo UV que `assets/sphere.obj`:
+// u = 0.5 + atan2(x, z) / 2π y v = 0.5 + asin(y) / π.
+
+// Origen de la malla de los cuerpos: un archivo OBJ o un generador.
+// Sintaxis: `ruta.obj`, o `nombre[:resolución]`, por ejemplo `icosphere:4`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MeshSource {
+    File(String),
+    // Segmentos en longitud; la latitud usa la mitad
+    UvSphere(usize),
+    // Subdivisiones del icosaedro
+    Icosphere(usize),
+    // Divisiones por arista de cada cara del cubo
+    CubeSphere(usize),
+    Plane(usize),
+    Torus(usize),
+    Cylinder(usize),
+}
+
+impl Default for MeshSource {
+    fn default() -> Self {
+        MeshSource::File(String::from("assets/sphere.obj"))
+    }
+}
+
+impl MeshSource {
+    // Los generadores dan una sola submalla sin material y sin avisos
+    pub fn load(&self) -> Result<(Vec<Submesh>, Vec<String>), String> {
+        let vertices = match *self {
+            MeshSource::File(ref path) => {
+                let obj = Obj::load(path).map_err(|err| err.to_string())?;
+                return Ok((obj.submeshes(), obj.warnings));
+            }
+            MeshSource::UvSphere(segments) => uv_sphere(segments, segments / 2),
+            MeshSource::Icosphere(subdivisions) => icosphere(subdivisions),
+            MeshSource::CubeSphere(divisions) => cube_sphere(divisions),
+            MeshSource::Plane(divisions) => plane(2.0, divisions),
+            MeshSource::Torus(segments) => torus(1.0, 0.35, segments, segments / 2),
+            MeshSource::Cylinder(segments) => cylinder(1.0, 2.0, segments),
+        };
+        Ok((vec![Submesh { material: None, vertices }], Vec::new()))
+    }
+}
+
+impl FromStr for MeshSource {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.ends_with(".obj") {
+            return Ok(MeshSource::File(s.to_string()));
+        }
+
+        let (name, resolution) = match s.split_once(':') {
+            Some((name, value)) => {
+                let value: usize = value.parse().map_err(|_| format!("Invalid mesh resolution: {}", s))?;
+                (name, Some(value))
+            }
+            None => (s, None),
+        };
+
+        // Valor por defecto y rango válido de la resolución
+        let resolution = |default: usize, min: usize, max: Option<usize>| {
+            let value = resolution.unwrap_or(default);
+            match max {
+                Some(max) if value < min || value > max => {
+                    Err(format!("Invalid mesh resolution: {} ({} must be between {} and {})", s, name, min, max))
+                }
+                None if value < min => Err(format!("Invalid mesh resolution: {} ({} must be at least {})", s, name, min)),
+                _ => Ok(value),
+            }
+        };
+
+        match name {
+            "uv_sphere" => Ok(MeshSource::UvSphere(resolution(32, 3, None)?)),
+            // Cada subdivisión multiplica los triángulos por 4
+            "icosphere" => Ok(MeshSource::Icosphere(resolution(3, 0, Some(7))?)),
+            "cube_sphere" => Ok(MeshSource::CubeSphere(resolution(8, 1, None)?)),
+            "plane" => Ok(MeshSource::Plane(resolution(1, 1, None)?)),
+            "torus" => Ok(MeshSource::Torus(resolution(48, 3, None)?)),
+            "cylinder" => Ok(MeshSource::Cylinder(resolution(32, 3, None)?)),
+            _ => Err(format!("Unknown mesh: {} (use FILE.obj, uv_sphere, icosphere, cube_sphere, plane, torus or cylinder)", s)),
+        }
+    }
+}
+
+// Agrega el triángulo en sentido antihorario respecto a la normal de sus
+// vértices; los degenerados (como los de los polos de una esfera UV) se descartan
+fn push_triangle(vertices: &mut Vec<Vertex>, a: Vertex, b: Vertex, c: Vertex) {
+    let cross = (b.position - a.position).cross(&(c.position - a.position));
+    if cross.magnitude_squared() <= f32::EPSILON * f32::EPSILON {
+        return;
+    }
+
+    if cross.dot(&(a.normal + b.normal + c.normal)) < 0.0 {
+        vertices.extend([a, c, b]);
+    } else {
+        vertices.extend([a, b, c]);
+    }
+}
+
+// Superficie paramétrica `surface(u, v) -> (posición, normal)` muestreada en una
+// rejilla de `columns` x `rows` celdas, con (u, v) como coordenadas de textura
+fn grid<F: Fn(f32, f32) -> (Vec3, Vec3)>(columns: usize, rows: usize, surface: F) -> Vec<Vertex> {
+    let vertex = |column: usize, row: usize| {
+        let u = column as f32 / columns as f32;
+        let v = row as f32 / rows as f32;
+        let (position, normal) = surface(u, v);
+        Vertex::new(position, normal, Vec3::new(u, v, 0.0))
+    };
+
+    let mut vertices = Vec::with_capacity(columns * rows * 6);
+    for row in 0..rows {
+        for column in 0..columns {
+            let a = vertex(column, row);
+            let b = vertex(column + 1, row);
+            let c = vertex(column + 1, row + 1);
+            let d = vertex(column, row + 1);
+            push_triangle(&mut vertices, a.clone(), b, c.clone());
+            push_triangle(&mut vertices, a, c, d);
+        }
+    }
+    vertices
+}
+
+fn sphere_uv(point: &Vec3) -> Vec2 {
+    Vec2::new(0.5 + point.x.atan2(point.z) / (2.0 * PI), 0.5 + point.y.clamp(-1.0, 1.0).asin() / PI)
+}
+
+// Triángulo sobre la esfera unitaria con normales y UV esféricas. Si cruza la
+// costura (u salta de 1 a 0) las u pequeñas se pasan a 1 + u, y en los polos,
+// donde u no está definida, se usa la media de los otros dos vértices.
+fn push_sphere_triangle(vertices: &mut Vec<Vertex>, a: Vec3, b: Vec3, c: Vec3) {
+    let points = [a.normalize(), b.normalize(), c.normalize()];
+    let mut uvs = points.map(|point| sphere_uv(&point));
+
+    let (min_u, max_u) = uvs.iter().fold((f32::MAX, f32::MIN), |(min, max), uv| (min.min(uv.x), max.max(uv.x)));
+    if max_u - min_u > 0.5 {
+        for uv in uvs.iter_mut().filter(|uv| uv.x < 0.5) {
+            uv.x += 1.0;
+        }
+    }
+
+    for pole in 0..3 {
+        if points[pole].y.abs() > 1.0 - 1e-5 {
+            let others: Vec<f32> = (0..3).filter(|&i| i != pole).map(|i| uvs[i].x).collect();
+            uvs[pole].x = (others[0] + others[1]) * 0.5;
+        }
+    }
+
+    let [a, b, c] = [0, 1, 2].map(|i| Vertex::new(points[i], points[i], Vec3::new(uvs[i].x, uvs[i].y, 0.0)));
+    push_triangle(vertices, a, b, c);
+}
+
+// Esfera de latitud y longitud, como `assets/sphere.obj` (32 x 16)
+pub fn uv_sphere(segments: usize, rings: usize) -> Vec<Vertex> {
+    grid(segments.max(3), rings.max(2), |u, v| {
+        let longitude = (u - 0.5) * 2.0 * PI;
+        let latitude = (v - 0.5) * PI;
+        let point = Vec3::new(latitude.cos() * longitude.sin(), latitude.sin(), latitude.cos() * longitude.cos());
+        (point, point)
+    })
+}
+
+// Icosaedro subdividido: triángulos de tamaño casi uniforme, sin polos
+pub fn icosphere(subdivisions: usize) -> Vec<Vertex> {
+    let t = (1.0 + 5.0_f32.sqrt()) / 2.0;
+    let corners = [
+        Vec3::new(-1.0, t, 0.0), Vec3::new(1.0, t, 0.0), Vec3::new(-1.0, -t, 0.0), Vec3::new(1.0, -t, 0.0),
+        Vec3::new(0.0, -1.0, t), Vec3::new(0.0, 1.0, t), Vec3::new(0.0, -1.0, -t), Vec3::new(0.0, 1.0, -t),
+        Vec3::new(t, 0.0, -1.0), Vec3::new(t, 0.0, 1.0), Vec3::new(-t, 0.0, -1.0), Vec3::new(-t, 0.0, 1.0),
+    ];
+    const FACES: [[usize; 3]; 20] = [
+        [0, 11, 5], [0, 5, 1], [0, 1, 7], [0, 7, 10], [0, 10, 11],
+        [1, 5, 9], [5, 11, 4], [11, 10, 2], [10, 7, 6], [7, 1, 8],
+        [3, 9, 4], [3, 4, 2], [3, 2, 6], [3, 6, 8], [3, 8, 9],
+        [4, 9, 5], [2, 4, 11], [6, 2, 10], [8, 6, 7], [9, 8, 1],
+    ];
+
+    let mut triangles: Vec<[Vec3; 3]> = FACES
+        .iter()
+        .map(|face| face.map(|index| corners[index].normalize()))
+        .collect();
+
+    // Cada triángulo se parte en cuatro con los puntos medios llevados a la esfera
+    for _ in 0..subdivisions {
+        triangles = triangles
+            .iter()
+            .flat_map(|&[a, b, c]| {
+                let ab = (a + b).normalize();
+                let bc = (b + c).normalize();
+                let ca = (c + a).normalize();
+                [[a, ab, ca], [b, bc, ab], [c, ca, bc], [ab, bc, ca]]
+            })
+            .collect();
+    }
+
+    let mut vertices = Vec::with_capacity(triangles.len() * 3);
+    for [a, b, c] in triangles {
+        push_sphere_triangle(&mut vertices, a, b, c);
+    }
+    vertices
+}
+
+// Cubo subdividido proyectado sobre la esfera. Se usa el mapeo que reparte las
+// celdas de forma más pareja que normalizar directamente
+pub fn cube_sphere(divisions: usize) -> Vec<Vertex> {
+    let divisions = divisions.max(1);
+    let spherify = |p: Vec3| {
+        let (x2, y2, z2) = (p.x * p.x, p.y * p.y, p.z * p.z);
+        Vec3::new(
+            p.x * (1.0 - y2 / 2.0 - z2 / 2.0 + y2 * z2 / 3.0).sqrt(),
+            p.y * (1.0 - z2 / 2.0 - x2 / 2.0 + z2 * x2 / 3.0).sqrt(),
+            p.z * (1.0 - x2 / 2.0 - y2 / 2.0 + x2 * y2 / 3.0).sqrt(),
+        )
+    };
+
+    let mut vertices = Vec::with_capacity(6 * divisions * divisions * 6);
+    for axis in 0..3 {
+        for sign in [-1.0, 1.0] {
+            // Ejes de la cara: el normal al cubo y los dos que recorren la cara
+            let mut normal = Vec3::zeros();
+            normal[axis] = sign;
+            let mut right = Vec3::zeros();
+            right[(axis + 1) % 3] = 1.0;
+            let mut up = Vec3::zeros();
+            up[(axis + 2) % 3] = 1.0;
+
+            let point = |i: usize, j: usize| {
+                let s = i as f32 / divisions as f32 * 2.0 - 1.0;
+                let t = j as f32 / divisions as f32 * 2.0 - 1.0;
+                spherify(normal + right * s + up * t)
+            };
+
+            for j in 0..divisions {
+                for i in 0..divisions {
+                    push_sphere_triangle(&mut vertices, point(i, j), point(i + 1, j), point(i + 1, j + 1));
+                    push_sphere_triangle(&mut vertices, point(i, j), point(i + 1, j + 1), point(i, j + 1));
+                }
+            }
+        }
+    }
+    vertices
+}
+
+// Cuadrado de lado `size` en el plano XZ con la normal hacia +y; v crece hacia -z
+pub fn plane(size: f32, divisions: usize) -> Vec<Vertex> {
+    let divisions = divisions.max(1);
+    grid(divisions, divisions, |u, v| {
+        (Vec3::new((u - 0.5) * size, 0.0, (0.5 - v) * size), Vec3::new(0.0, 1.0, 0.0))
+    })
+}
+
+// Toro alrededor del eje y: u recorre el anillo mayor y v el tubo
+pub fn torus(major_radius: f32, minor_radius: f32, segments: usize, sides: usize) -> Vec<Vertex> {
+    grid(segments.max(3), sides.max(3), |u, v| {
+        let around = u * 2.0 * PI;
+        let tube = v * 2.0 * PI;
+        let center = Vec3::new(around.sin(), 0.0, around.cos()) * major_radius;
+        let normal = Vec3::new(around.sin() * tube.cos(), tube.sin(), around.cos() * tube.cos());
+        (center + normal * minor_radius, normal)
+    })
+}
+
+// Cilindro alrededor del eje y, centrado en el origen y con tapas. El lateral usa
+// la u de la esfera y v a lo alto; las tapas, una proyección plana
+pub fn cylinder(radius: f32, height: f32, segments: usize) -> Vec<Vertex> {
+    let segments = segments.max(3);
+    let half = height / 2.0;
+    let mut vertices = grid(segments, 1, |u, v| {
+        let longitude = (u - 0.5) * 2.0 * PI;
+        let normal = Vec3::new(longitude.sin(), 0.0, longitude.cos());
+        (normal * radius + Vec3::new(0.0, (v - 0.5) * height, 0.0), normal)
+    });
+
+    for sign in [-1.0, 1.0] {
+        let normal = Vec3::new(0.0, sign, 0.0);
+        let cap = |angle: f32, scale: f32| {
+            let (sin, cos) = angle.sin_cos();
+            let position = Vec3::new(sin * radius * scale, sign * half, cos * radius * scale);
+            Vertex::new(position, normal, Vec3::new(0.5 + sin * scale * 0.5, 0.5 - cos * scale * 0.5, 0.0))
+        };
+
+        for segment in 0..segments {
+            let a = segment as f32 / segments as f32 * 2.0 * PI;
+            let b = (segment + 1) as f32 / segments as f32 * 2.0 * PI;
+            push_triangle(&mut vertices, cap(0.0, 0.0), cap(a, 1.0), cap(b, 1.0));
+        }
+    }
+    vertices
+}
+
+// Anillo plano en el plano XZ con la normal hacia +y. La u de las UV es
+// radial (0 en el borde interior, 1 en el exterior) y la v da la vuelta.
+pub fn annulus(inner_radius: f32, outer_radius: f32, segments: usize) -> Vec<Vertex> {
+    grid(1, segments.max(3), |u, v| {
+        let radius = inner_radius + (outer_radius - inner_radius) * u;
+        let angle = v * 2.0 * PI;
+        (Vec3::new(radius * angle.cos(), 0.0, -radius * angle.sin()), Vec3::new(0.0, 1.0, 0.0))
+    })
+}
@@ -1,112 +1,437 @@
-use nalgebra_glm::Vec3;
+use nalgebra_glm::{Vec2, Vec3};
 use crate::vertex::Vertex;
+use std::fmt;
 use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::io::{self, BufRead, BufReader};
+use std::path::Path;
+
+// Índices ya resueltos (base 0) de una esquina de cara: `v`, `v/vt`, `v//vn` o `v/vt/vn`
+#[derive(Debug, Clone, Copy)]
+struct FaceVertex {
+    position: usize,
+    texcoord: Option<usize>,
+    normal: Option<usize>,
+}
+
+struct Face {
+    corners: [FaceVertex; 3],
+    // Índice en `Obj::materials` activo con `usemtl` al leer la cara
+    material: Option<usize>,
+}
 
 pub struct Obj {
     vertices: Vec<Vec3>,
     normals: Vec<Vec3>,
     texcoords: Vec<Vec3>,
-    faces: Vec<(Vec3, Vec3, Vec3)>,
+    faces: Vec<Face>,
+    pub materials: Vec<MtlMaterial>,
+    // Avisos no fatales de la carga; quien llama decide cómo mostrarlos
+    pub warnings: Vec<String>,
 }
 
-impl Obj {
-    pub fn load(filename: &str) -> Result<Self, std::io::Error> {
-        let file = File::open(filename)?;
-        let reader = BufReader::new(file);
+// Material de un archivo MTL. `diffuse_map` ya viene resuelto respecto a la
+// carpeta del MTL.
+#[derive(Debug, Clone)]
+pub struct MtlMaterial {
+    pub name: String,
+    // Kd
+    pub diffuse: Vec3,
+    // Ks
+    pub specular: Vec3,
+    // Ns
+    pub shininess: f32,
+    // Ke
+    pub emission: Vec3,
+    // d, o 1 - Tr
+    pub opacity: f32,
+    // map_Kd
+    pub diffuse_map: Option<String>,
+}
 
-        let mut vertices = Vec::new();
-        let mut normals = Vec::new();
-        let mut texcoords = Vec::new();
-        let mut faces = Vec::new();
+impl MtlMaterial {
+    fn new(name: &str) -> Self {
+        MtlMaterial {
+            name: name.to_string(),
+            diffuse: Vec3::new(0.8, 0.8, 0.8),
+            specular: Vec3::zeros(),
+            shininess: 1.0,
+            emission: Vec3::zeros(),
+            opacity: 1.0,
+            diffuse_map: None,
+        }
+    }
+}
 
-        for line in reader.lines() {
-            let line = line?;
-            let parts: Vec<&str> = line.split_whitespace().collect();
+// Triángulos que comparten material, listos para dibujar con un mismo shader
+pub struct Submesh {
+    pub material: Option<MtlMaterial>,
+    pub vertices: Vec<Vertex>,
+}
 
-            if parts.is_empty() {
-                continue;
+#[derive(Debug)]
+pub enum ObjErrorKind {
+    Io(io::Error),
+    InvalidNumber(String),
+    MissingValues { statement: &'static str, expected: usize },
+    InvalidIndex(String),
+    // Índice fuera de las posiciones, UV o normales definidas hasta esa línea
+    IndexOutOfRange { kind: &'static str, index: i64, count: usize },
+    FaceTooSmall(usize),
+    // Propiedad de material antes de cualquier `newmtl`
+    NoMaterial(String),
+}
+
+// Error con el archivo y, salvo al abrirlo, la línea (desde 1)
+#[derive(Debug)]
+pub struct ObjError {
+    pub path: String,
+    pub line: Option<usize>,
+    pub kind: ObjErrorKind,
+}
+
+impl fmt::Display for ObjErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ObjErrorKind::Io(err) => write!(f, "{}", err),
+            ObjErrorKind::InvalidNumber(value) => write!(f, "Invalid number '{}'", value),
+            ObjErrorKind::MissingValues { statement, expected } => {
+                write!(f, "'{}' needs at least {} values", statement, expected)
+            }
+            ObjErrorKind::InvalidIndex(value) => write!(f, "Invalid face index '{}'", value),
+            ObjErrorKind::IndexOutOfRange { kind, index, count } => {
+                write!(f, "{} index {} out of range ({} defined)", kind, index, count)
             }
+            ObjErrorKind::FaceTooSmall(count) => write!(f, "Face with {} vertices (at least 3 needed)", count),
+            ObjErrorKind::NoMaterial(statement) => write!(f, "'{}' before any newmtl", statement),
+        }
+    }
+}
 
-            match parts[0] {
-                "v" if parts.len() >= 4 => {
-                    vertices.push(Vec3::new(
-                        parts[1].parse().unwrap_or(0.0),
-                        parts[2].parse().unwrap_or(0.0),
-                        parts[3].parse().unwrap_or(0.0),
-                    ));
-                }
-                "vn" if parts.len() >= 4 => {
-                    normals.push(Vec3::new(
-                        parts[1].parse().unwrap_or(0.0),
-                        parts[2].parse().unwrap_or(0.0),
-                        parts[3].parse().unwrap_or(0.0),
-                    ));
+impl fmt::Display for ObjError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "{}:{}: {}", self.path, line, self.kind),
+            None => write!(f, "{}: {}", self.path, self.kind),
+        }
+    }
+}
+
+impl std::error::Error for ObjError {}
+
+impl Obj {
+    pub fn load(filename: &str) -> Result<Self, ObjError> {
+        let file = File::open(filename).map_err(|err| ObjError {
+            path: filename.to_string(),
+            line: None,
+            kind: ObjErrorKind::Io(err),
+        })?;
+        Obj::parse(BufReader::new(file), filename)
+    }
+
+    pub fn parse<R: BufRead>(reader: R, source: &str) -> Result<Self, ObjError> {
+        let mut obj = Obj {
+            vertices: Vec::new(),
+            normals: Vec::new(),
+            texcoords: Vec::new(),
+            faces: Vec::new(),
+            materials: Vec::new(),
+            warnings: Vec::new(),
+        };
+        let mut material = None;
+        // Materiales desconocidos ya avisados, para no repetir el aviso en cada `usemtl`
+        let mut missing: Vec<String> = Vec::new();
+
+        for (number, line) in reader.lines().enumerate() {
+            let error = |kind: ObjErrorKind| ObjError {
+                path: source.to_string(),
+                line: Some(number + 1),
+                kind,
+            };
+            let line = line.map_err(|err| error(ObjErrorKind::Io(err)))?;
+            let line = line.split('#').next().unwrap_or("");
+            let mut parts = line.split_whitespace();
+            let Some(statement) = parts.next() else {
+                continue;
+            };
+            let values: Vec<&str> = parts.collect();
+
+            match statement {
+                "v" => obj.vertices.push(parse_vec3(&values, "v", 3).map_err(error)?),
+                "vn" => obj.normals.push(parse_vec3(&values, "vn", 3).map_err(error)?),
+                // La coordenada v es opcional y la w se ignora
+                "vt" => obj.texcoords.push(parse_vec3(&values, "vt", 1).map_err(error)?),
+                "f" => {
+                    if values.len() < 3 {
+                        return Err(error(ObjErrorKind::FaceTooSmall(values.len())));
+                    }
+                    let corners = values
+                        .iter()
+                        .map(|value| obj.parse_face_vertex(value))
+                        .collect::<Result<Vec<_>, _>>()
+                        .map_err(error)?;
+                    obj.triangulate(&corners, material);
                 }
-                "vt" if parts.len() >= 3 => {
-                    texcoords.push(Vec3::new(
-                        parts[1].parse().unwrap_or(0.0),
-                        parts[2].parse().unwrap_or(0.0),
-                        0.0,
-                    ));
+                // Las bibliotecas se buscan junto al OBJ; un material redefinido reemplaza al
+                // anterior. Muchos OBJ exportados nombran bibliotecas que no se distribuyen:
+                // sin el archivo solo se avisa y esas caras usan el shader del nodo.
+                "mtllib" => {
+                    if values.is_empty() {
+                        return Err(error(ObjErrorKind::MissingValues { statement: "mtllib", expected: 1 }));
+                    }
+                    for library in &values {
+                        let library = relative_to(source, library);
+                        let materials = match load_mtl(&library) {
+                            Ok(materials) => materials,
+                            Err(ObjError { kind: ObjErrorKind::Io(err), line: None, .. }) => {
+                                obj.warnings.push(format!("{}:{}: cannot read material library {}: {}", source, number + 1, library, err));
+                                continue;
+                            }
+                            Err(err) => return Err(err),
+                        };
+                        for loaded in materials {
+                            match obj.materials.iter().position(|existing| existing.name == loaded.name) {
+                                Some(index) => obj.materials[index] = loaded,
+                                None => obj.materials.push(loaded),
+                            }
+                        }
+                    }
                 }
-                "f" if parts.len() >= 4 => {
-                    let parse_face = |s: &str| -> Vec3 {
-                        let indices: Vec<&str> = s.split('/').collect();
-                        Vec3::new(
-                            indices.get(0).and_then(|s| s.parse().ok()).unwrap_or(0.0),
-                            indices.get(1).and_then(|s| s.parse().ok()).unwrap_or(0.0),
-                            indices.get(2).and_then(|s| s.parse().ok()).unwrap_or(0.0),
-                        )
-                    };
-
-                    let face1 = parse_face(parts[1]);
-                    let face2 = parse_face(parts[2]);
-                    let face3 = parse_face(parts[3]);
-
-                    faces.push((face1, face2, face3));
+                "usemtl" => {
+                    let name = values.first().ok_or_else(|| error(ObjErrorKind::MissingValues { statement: "usemtl", expected: 1 }))?;
+                    material = obj.materials.iter().position(|existing| existing.name == *name);
+                    if material.is_none() && !missing.iter().any(|warned| warned == name) {
+                        obj.warnings.push(format!("{}:{}: unknown material '{}', using the node's shader", source, number + 1, name));
+                        missing.push(name.to_string());
+                    }
                 }
+                // Grupos, objetos y suavizado no afectan la geometría
                 _ => {}
             }
         }
 
-        Ok(Obj {
-            vertices,
-            normals,
-            texcoords,
-            faces,
+        Ok(obj)
+    }
+
+    fn parse_face_vertex(&self, value: &str) -> Result<FaceVertex, ObjErrorKind> {
+        let mut fields = value.split('/');
+        let position = fields.next().unwrap_or("");
+        let texcoord = fields.next().filter(|field| !field.is_empty());
+        let normal = fields.next().filter(|field| !field.is_empty());
+        if fields.next().is_some() {
+            return Err(ObjErrorKind::InvalidIndex(value.to_string()));
+        }
+
+        let resolve = |field: &str, kind: &'static str, count: usize| {
+            let index: i64 = field.parse().map_err(|_| ObjErrorKind::InvalidIndex(value.to_string()))?;
+            // Positivos desde 1; negativos relativos al último elemento definido
+            let resolved = if index > 0 { index - 1 } else { count as i64 + index };
+            if index == 0 || resolved < 0 || resolved >= count as i64 {
+                return Err(ObjErrorKind::IndexOutOfRange { kind, index, count });
+            }
+            Ok(resolved as usize)
+        };
+
+        Ok(FaceVertex {
+            position: resolve(position, "Vertex", self.vertices.len())?,
+            texcoord: texcoord.map(|field| resolve(field, "Texture coordinate", self.texcoords.len())).transpose()?,
+            normal: normal.map(|field| resolve(field, "Normal", self.normals.len())).transpose()?,
         })
     }
 
-    pub fn get_vertex_array(&self) -> Vec<Vertex> {
-        let mut vertex_array = Vec::new();
+    // Parte el polígono en triángulos recortando orejas sobre el plano del
+    // polígono; así también se cubren caras cóncavas. Si no encuentra una
+    // oreja (polígono degenerado o que se cruza) termina en abanico.
+    fn triangulate(&mut self, corners: &[FaceVertex], material: Option<usize>) {
+        if corners.len() == 3 {
+            self.faces.push(Face { corners: [corners[0], corners[1], corners[2]], material });
+            return;
+        }
+
+        let positions: Vec<Vec3> = corners.iter().map(|corner| self.vertices[corner.position]).collect();
+        let normal = newell_normal(&positions);
+
+        // Proyección al plano quitando el eje dominante de la normal
+        let axis = normal.iamax();
+        let project = |p: &Vec3| match axis {
+            0 => Vec2::new(p.y, p.z),
+            1 => Vec2::new(p.z, p.x),
+            _ => Vec2::new(p.x, p.y),
+        };
+        let points: Vec<Vec2> = positions.iter().map(project).collect();
+        let orientation = if normal[axis] >= 0.0 { 1.0 } else { -1.0 };
+
+        let mut remaining: Vec<usize> = (0..corners.len()).collect();
+        while remaining.len() > 3 {
+            let count = remaining.len();
+            let ear = (0..count).find(|&i| {
+                let (a, b, c) = (remaining[(i + count - 1) % count], remaining[i], remaining[(i + 1) % count]);
+                let area = cross_2d(points[b] - points[a], points[c] - points[a]) * orientation;
+                area > 0.0
+                    && remaining
+                        .iter()
+                        .filter(|&&other| other != a && other != b && other != c)
+                        .all(|&other| !inside_triangle(points[other], points[a], points[b], points[c], orientation))
+            });
+
+            let Some(i) = ear else {
+                break;
+            };
+            let (a, b, c) = (remaining[(i + count - 1) % count], remaining[i], remaining[(i + 1) % count]);
+            self.faces.push(Face { corners: [corners[a], corners[b], corners[c]], material });
+            remaining.remove(i);
+        }
+
+        for i in 1..remaining.len() - 1 {
+            self.faces.push(Face {
+                corners: [corners[remaining[0]], corners[remaining[i]], corners[remaining[i + 1]]],
+                material,
+            });
+        }
+    }
+
+    // Una submalla por material, en el orden en que aparecen; las caras sin
+    // `usemtl` van juntas en una submalla sin material
+    pub fn submeshes(&self) -> Vec<Submesh> {
+        let mut groups: Vec<(Option<usize>, Vec<Vertex>)> = Vec::new();
 
         for face in &self.faces {
-            for i in 0..3 {
-                let face_indices = if i == 0 { face.0 } else if i == 1 { face.1 } else { face.2 };
-                
-                let position = if face_indices.x > 0.0 && (face_indices.x as usize) <= self.vertices.len() {
-                    self.vertices[(face_indices.x - 1.0) as usize]
-                } else {
-                    Vec3::zeros()
-                };
-
-                let texcoord = if face_indices.y > 0.0 && (face_indices.y as usize) <= self.texcoords.len() {
-                    self.texcoords[(face_indices.y - 1.0) as usize]
-                } else {
-                    Vec3::zeros()
-                };
-
-                let normal = if face_indices.z > 0.0 && (face_indices.z as usize) <= self.normals.len() {
-                    self.normals[(face_indices.z - 1.0) as usize]
-                } else {
-                    Vec3::zeros()
-                };
-
-                vertex_array.push(Vertex::new(position, normal, texcoord));
+            let group = match groups.iter().position(|(material, _)| *material == face.material) {
+                Some(group) => group,
+                None => {
+                    groups.push((face.material, Vec::new()));
+                    groups.len() - 1
+                }
+            };
+            groups[group].1.extend(self.face_vertices(face));
+        }
+
+        groups
+            .into_iter()
+            .map(|(material, vertices)| Submesh {
+                material: material.map(|index| self.materials[index].clone()),
+                vertices,
+            })
+            .collect()
+    }
+
+    // Las caras sin normales usan la normal geométrica del triángulo
+    fn face_vertices(&self, face: &Face) -> [Vertex; 3] {
+        let positions = face.corners.map(|corner| self.vertices[corner.position]);
+        let face_normal = (positions[1] - positions[0])
+            .cross(&(positions[2] - positions[0]))
+            .try_normalize(f32::EPSILON)
+            .unwrap_or(Vec3::zeros());
+
+        std::array::from_fn(|i| {
+            let corner = face.corners[i];
+            let texcoord = corner.texcoord.map_or(Vec3::zeros(), |index| self.texcoords[index]);
+            let normal = corner.normal.map_or(face_normal, |index| self.normals[index]);
+            Vertex::new(positions[i], normal, texcoord)
+        })
+    }
+}
+
+// Lee las propiedades que usa el renderer (Kd, Ks, Ns, Ke, d/Tr y map_Kd); el resto se ignora
+pub fn load_mtl(path: &str) -> Result<Vec<MtlMaterial>, ObjError> {
+    let file = File::open(path).map_err(|err| ObjError {
+        path: path.to_string(),
+        line: None,
+        kind: ObjErrorKind::Io(err),
+    })?;
+
+    let mut materials: Vec<MtlMaterial> = Vec::new();
+    for (number, line) in BufReader::new(file).lines().enumerate() {
+        let error = |kind: ObjErrorKind| ObjError {
+            path: path.to_string(),
+            line: Some(number + 1),
+            kind,
+        };
+        let line = line.map_err(|err| error(ObjErrorKind::Io(err)))?;
+        let line = line.split('#').next().unwrap_or("");
+        let mut parts = line.split_whitespace();
+        let Some(statement) = parts.next() else {
+            continue;
+        };
+        let values: Vec<&str> = parts.collect();
+
+        if statement == "newmtl" {
+            let name = values.first().ok_or_else(|| error(ObjErrorKind::MissingValues { statement: "newmtl", expected: 1 }))?;
+            materials.push(MtlMaterial::new(name));
+            continue;
+        }
+
+        let known = ["Kd", "Ks", "Ns", "Ke", "d", "Tr", "map_Kd"];
+        if !known.contains(&statement) {
+            continue;
+        }
+        let material = materials
+            .last_mut()
+            .ok_or_else(|| error(ObjErrorKind::NoMaterial(statement.to_string())))?;
+        match statement {
+            "Kd" => material.diffuse = parse_color(&values, "Kd").map_err(error)?,
+            "Ks" => material.specular = parse_color(&values, "Ks").map_err(error)?,
+            "Ke" => material.emission = parse_color(&values, "Ke").map_err(error)?,
+            "Ns" => material.shininess = parse_vec3(&values, "Ns", 1).map_err(error)?.x,
+            "d" => material.opacity = parse_vec3(&values, "d", 1).map_err(error)?.x,
+            "Tr" => material.opacity = 1.0 - parse_vec3(&values, "Tr", 1).map_err(error)?.x,
+            // Las opciones como `-s 1 1 1` van antes del nombre del archivo
+            _ => {
+                let file = values.last().ok_or_else(|| error(ObjErrorKind::MissingValues { statement: "map_Kd", expected: 1 }))?;
+                material.diffuse_map = Some(relative_to(path, file));
             }
         }
+    }
+
+    Ok(materials)
+}
+
+// Ruta de un archivo referenciado desde `source`, relativa a su carpeta
+fn relative_to(source: &str, file: &str) -> String {
+    match Path::new(source).parent() {
+        Some(folder) => folder.join(file).to_string_lossy().into_owned(),
+        None => file.to_string(),
+    }
+}
+
+// `r g b`, o un solo valor para los tres canales
+fn parse_color(values: &[&str], statement: &'static str) -> Result<Vec3, ObjErrorKind> {
+    let color = parse_vec3(values, statement, 1)?;
+    if values.len() < 3 {
+        return Ok(Vec3::new(color.x, color.x, color.x));
+    }
+    Ok(color)
+}
 
-        vertex_array
+fn parse_vec3(values: &[&str], statement: &'static str, required: usize) -> Result<Vec3, ObjErrorKind> {
+    if values.len() < required {
+        return Err(ObjErrorKind::MissingValues { statement, expected: required });
     }
-}
\ No newline at end of file
+
+    let mut vector = Vec3::zeros();
+    for (component, value) in vector.iter_mut().zip(values) {
+        *component = value.parse().map_err(|_| ObjErrorKind::InvalidNumber(value.to_string()))?;
+    }
+    Ok(vector)
+}
+
+// Normal del polígono por el método de Newell; sirve aunque no sea plano ni convexo
+fn newell_normal(points: &[Vec3]) -> Vec3 {
+    let mut normal = Vec3::zeros();
+    for (i, current) in points.iter().enumerate() {
+        let next = points[(i + 1) % points.len()];
+        normal.x += (current.y - next.y) * (current.z + next.z);
+        normal.y += (current.z - next.z) * (current.x + next.x);
+        normal.z += (current.x - next.x) * (current.y + next.y);
+    }
+    normal
+}
+
+fn cross_2d(a: Vec2, b: Vec2) -> f32 {
+    a.x * b.y - a.y * b.x
+}
+
+fn inside_triangle(p: Vec2, a: Vec2, b: Vec2, c: Vec2, orientation: f32) -> bool {
+    cross_2d(b - a, p - a) * orientation >= 0.0
+        && cross_2d(c - b, p - b) * orientation >= 0.0
+        && cross_2d(a - c, p - c) * orientation >= 0.0
+}
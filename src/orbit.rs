@@ -0,0 +1,73 @@
+use std::f32::consts::PI;
+use nalgebra_glm::Vec3;
+
+// Elementos orbitales keplerianos. Los ángulos se guardan en radianes y el
+// plano de referencia es XZ (y hacia arriba).
+#[derive(Debug, Clone, Copy)]
+pub struct Orbit {
+    pub semi_major_axis: f32,
+    pub eccentricity: f32,
+    pub inclination: f32,
+    // Longitud del nodo ascendente
+    pub ascending_node: f32,
+    pub argument_of_periapsis: f32,
+    // Anomalía media en t = 0
+    pub mean_anomaly: f32,
+    // Segundos de simulación por vuelta
+    pub period: f32,
+}
+
+impl Default for Orbit {
+    fn default() -> Self {
+        Orbit {
+            semi_major_axis: 1.0,
+            eccentricity: 0.0,
+            inclination: 0.0,
+            ascending_node: 0.0,
+            argument_of_periapsis: 0.0,
+            mean_anomaly: 0.0,
+            period: 1.0,
+        }
+    }
+}
+
+impl Orbit {
+    // Posición relativa al cuerpo central en el instante `time`
+    pub fn position(&self, time: f32) -> Vec3 {
+        let e = self.eccentricity.clamp(0.0, 0.99);
+        let mean = self.mean_anomaly + 2.0 * PI * time / self.period;
+        let eccentric = eccentric_anomaly(mean.rem_euclid(2.0 * PI), e);
+
+        // Coordenadas en el plano de la órbita, con el periapsis sobre +x
+        let x = self.semi_major_axis * (eccentric.cos() - e);
+        let y = self.semi_major_axis * (1.0 - e * e).sqrt() * eccentric.sin();
+
+        // Periapsis, inclinación y nodo ascendente (rotaciones z-x-z clásicas),
+        // con la z de la astronomía convertida a la y de la escena
+        let (sin_w, cos_w) = self.argument_of_periapsis.sin_cos();
+        let (sin_i, cos_i) = self.inclination.sin_cos();
+        let (sin_o, cos_o) = self.ascending_node.sin_cos();
+
+        let px = x * cos_w - y * sin_w;
+        let py = x * sin_w + y * cos_w;
+
+        let ox = px * cos_o - py * cos_i * sin_o;
+        let oy = px * sin_o + py * cos_i * cos_o;
+        let oz = py * sin_i;
+
+        Vec3::new(ox, oz, -oy)
+    }
+}
+
+// Resuelve la ecuación de Kepler M = E - e sin E con Newton-Raphson
+fn eccentric_anomaly(mean: f32, eccentricity: f32) -> f32 {
+    let mut eccentric = if eccentricity > 0.8 { PI } else { mean };
+    for _ in 0..8 {
+        let delta = (eccentric - eccentricity * eccentric.sin() - mean) / (1.0 - eccentricity * eccentric.cos());
+        eccentric -= delta;
+        if delta.abs() < 1e-6 {
+            break;
+        }
+    }
+    eccentric
+}
@@ -0,0 +1,350 @@
+use std::fs;
+use rayon::prelude::*;
+use crate::color::Color;
+use crate::framebuffer::Framebuffer;
+use crate::{linearize_depth, FAR_PLANE};
+
+pub const DEFAULT_CONFIG: &str = "assets/postprocess.cfg";
+
+// Datos de solo lectura que recibe cada efecto
+pub struct PostContext<'a> {
+    pub width: usize,
+    pub height: usize,
+    // Profundidad NDC por píxel (INFINITY donde no hay geometría)
+    pub depth: &'a [f32],
+    pub time: f32,
+}
+
+impl PostContext<'_> {
+    // Distancia a la cámara en unidades de mundo; el fondo queda en el plano lejano
+    pub fn linear_depth(&self, index: usize) -> f32 {
+        let z = self.depth[index];
+        if !z.is_finite() {
+            return FAR_PLANE;
+        }
+        linearize_depth(z)
+    }
+
+    fn sample(&self, input: &[Color], x: f32, y: f32) -> Color {
+        let x = (x.round() as isize).clamp(0, self.width as isize - 1) as usize;
+        let y = (y.round() as isize).clamp(0, self.height as isize - 1) as usize;
+        input[y * self.width + x]
+    }
+}
+
+// Un efecto lee `input` (y la profundidad del contexto) y escribe cada píxel de `output`
+pub trait PostProcess: Send + Sync {
+    fn name(&self) -> &'static str;
+    fn apply(&self, input: &[Color], output: &mut [Color], context: &PostContext);
+    fn set_parameter(&mut self, key: &str, value: f32) -> Result<(), String>;
+}
+
+// Recorre `output` por filas en paralelo pasando las coordenadas de cada píxel
+fn for_each_pixel<F>(output: &mut [Color], context: &PostContext, shade: F)
+where
+    F: Fn(usize, usize) -> Color + Sync,
+{
+    output.par_chunks_mut(context.width).enumerate().for_each(|(y, row)| {
+        for (x, pixel) in row.iter_mut().enumerate() {
+            *pixel = shade(x, y);
+        }
+    });
+}
+
+fn unknown_parameter(effect: &str, key: &str) -> String {
+    format!("Unknown parameter '{}' for {}", key, effect)
+}
+
+pub struct Vignette {
+    pub strength: f32,
+    // Distancia al centro (0 = centro, 1 = esquina) donde empieza a oscurecer
+    pub radius: f32,
+    pub softness: f32,
+}
+
+impl PostProcess for Vignette {
+    fn name(&self) -> &'static str {
+        "vignette"
+    }
+
+    fn apply(&self, input: &[Color], output: &mut [Color], context: &PostContext) {
+        let half_width = context.width as f32 * 0.5;
+        let half_height = context.height as f32 * 0.5;
+        let corner = (half_width * half_width + half_height * half_height).sqrt();
+
+        for_each_pixel(output, context, |x, y| {
+            let dx = x as f32 + 0.5 - half_width;
+            let dy = y as f32 + 0.5 - half_height;
+            let distance = (dx * dx + dy * dy).sqrt() / corner;
+            let t = ((distance - self.radius) / self.softness.max(1e-4)).clamp(0.0, 1.0);
+            let falloff = t * t * (3.0 - 2.0 * t);
+            input[y * context.width + x] * (1.0 - self.strength * falloff)
+        });
+    }
+
+    fn set_parameter(&mut self, key: &str, value: f32) -> Result<(), String> {
+        match key {
+            "strength" => self.strength = value,
+            "radius" => self.radius = value,
+            "softness" => self.softness = value,
+            _ => return Err(unknown_parameter(self.name(), key)),
+        }
+        Ok(())
+    }
+}
+
+pub struct FilmGrain {
+    pub amount: f32,
+}
+
+impl PostProcess for FilmGrain {
+    fn name(&self) -> &'static str {
+        "film_grain"
+    }
+
+    fn apply(&self, input: &[Color], output: &mut [Color], context: &PostContext) {
+        // El patrón cambia con cada cuadro
+        let seed = (context.time * 60.0) as u32;
+
+        for_each_pixel(output, context, |x, y| {
+            let noise = hash(x as u32, y as u32, seed) * 2.0 - 1.0;
+            let color = input[y * context.width + x];
+            // Más grano en los tonos medios que en las luces
+            let weight = 1.0 - color.luminance().clamp(0.0, 1.0) * 0.5;
+            color * (1.0 + noise * self.amount * weight)
+        });
+    }
+
+    fn set_parameter(&mut self, key: &str, value: f32) -> Result<(), String> {
+        match key {
+            "amount" => self.amount = value,
+            _ => return Err(unknown_parameter(self.name(), key)),
+        }
+        Ok(())
+    }
+}
+
+// Hash entero a [0, 1)
+fn hash(x: u32, y: u32, seed: u32) -> f32 {
+    let mut h = x.wrapping_mul(0x8da6b343) ^ y.wrapping_mul(0xd8163841) ^ seed.wrapping_mul(0xcb1ab31f);
+    h ^= h >> 15;
+    h = h.wrapping_mul(0x2c1b3c6d);
+    h ^= h >> 12;
+    (h >> 8) as f32 / (1u32 << 24) as f32
+}
+
+pub struct ChromaticAberration {
+    // Desplazamiento de rojo y azul en la esquina, como fracción del ancho
+    pub strength: f32,
+}
+
+impl PostProcess for ChromaticAberration {
+    fn name(&self) -> &'static str {
+        "chromatic_aberration"
+    }
+
+    fn apply(&self, input: &[Color], output: &mut [Color], context: &PostContext) {
+        let center_x = context.width as f32 * 0.5;
+        let center_y = context.height as f32 * 0.5;
+        let scale = self.strength * context.width as f32 / center_x.max(center_y);
+
+        for_each_pixel(output, context, |x, y| {
+            // El desplazamiento crece hacia los bordes
+            let dx = (x as f32 + 0.5 - center_x) / center_x * scale;
+            let dy = (y as f32 + 0.5 - center_y) / center_x * scale;
+            let red = context.sample(input, x as f32 + dx, y as f32 + dy);
+            let blue = context.sample(input, x as f32 - dx, y as f32 - dy);
+            let green = input[y * context.width + x];
+            Color { r: red.r, g: green.g, b: blue.b }
+        });
+    }
+
+    fn set_parameter(&mut self, key: &str, value: f32) -> Result<(), String> {
+        match key {
+            "strength" => self.strength = value,
+            _ => return Err(unknown_parameter(self.name(), key)),
+        }
+        Ok(())
+    }
+}
+
+pub struct ColorGrading {
+    pub saturation: f32,
+    // Contraste alrededor del gris medio lineal (0.18)
+    pub contrast: f32,
+    // Positivo calienta (más rojo), negativo enfría (más azul)
+    pub temperature: f32,
+    pub gain: f32,
+}
+
+impl PostProcess for ColorGrading {
+    fn name(&self) -> &'static str {
+        "color_grading"
+    }
+
+    fn apply(&self, input: &[Color], output: &mut [Color], context: &PostContext) {
+        const MIDDLE_GRAY: f32 = 0.18;
+        let warm = Color::from_float(1.0 + self.temperature * 0.1, 1.0, 1.0 - self.temperature * 0.1);
+
+        for_each_pixel(output, context, |x, y| {
+            let color = input[y * context.width + x] * self.gain;
+            let luminance = color.luminance();
+            let grade = |c: f32, tint: f32| {
+                let saturated = luminance + (c - luminance) * self.saturation;
+                let contrasted = MIDDLE_GRAY * (saturated.max(0.0) / MIDDLE_GRAY).powf(self.contrast);
+                contrasted * tint
+            };
+            Color::from_float(grade(color.r, warm.r), grade(color.g, warm.g), grade(color.b, warm.b))
+        });
+    }
+
+    fn set_parameter(&mut self, key: &str, value: f32) -> Result<(), String> {
+        match key {
+            "saturation" => self.saturation = value,
+            "contrast" => self.contrast = value,
+            "temperature" => self.temperature = value,
+            "gain" => self.gain = value,
+            _ => return Err(unknown_parameter(self.name(), key)),
+        }
+        Ok(())
+    }
+}
+
+// Niebla exponencial según la profundidad lineal; el fondo no se modifica
+pub struct DepthFog {
+    pub density: f32,
+    // Distancia a partir de la cual empieza la niebla
+    pub start: f32,
+    pub color: Color,
+}
+
+impl PostProcess for DepthFog {
+    fn name(&self) -> &'static str {
+        "depth_fog"
+    }
+
+    fn apply(&self, input: &[Color], output: &mut [Color], context: &PostContext) {
+        for_each_pixel(output, context, |x, y| {
+            let index = y * context.width + x;
+            let color = input[index];
+            if !context.depth[index].is_finite() {
+                return color;
+            }
+
+            let distance = (context.linear_depth(index) - self.start).max(0.0);
+            let visibility = (-distance * self.density).exp();
+            color * visibility + self.color * (1.0 - visibility)
+        });
+    }
+
+    fn set_parameter(&mut self, key: &str, value: f32) -> Result<(), String> {
+        match key {
+            "density" => self.density = value,
+            "start" => self.start = value,
+            "r" => self.color.r = value,
+            "g" => self.color.g = value,
+            "b" => self.color.b = value,
+            _ => return Err(unknown_parameter(self.name(), key)),
+        }
+        Ok(())
+    }
+}
+
+pub fn create_effect(name: &str) -> Option<Box<dyn PostProcess>> {
+    let effect: Box<dyn PostProcess> = match name {
+        "vignette" => Box::new(Vignette { strength: 0.6, radius: 0.4, softness: 0.6 }),
+        "film_grain" => Box::new(FilmGrain { amount: 0.05 }),
+        "chromatic_aberration" => Box::new(ChromaticAberration { strength: 0.003 }),
+        "color_grading" => Box::new(ColorGrading { saturation: 1.0, contrast: 1.0, temperature: 0.0, gain: 1.0 }),
+        "depth_fog" => Box::new(DepthFog { density: 0.02, start: 0.0, color: Color::black() }),
+        _ => return None,
+    };
+    Some(effect)
+}
+
+pub struct PostEffect {
+    pub enabled: bool,
+    pub effect: Box<dyn PostProcess>,
+}
+
+// Lista ordenada de efectos que se aplican sobre `hdr_buffer` antes del tone mapping.
+// Cada efecto escribe en el buffer auxiliar y luego se intercambian (ping-pong).
+#[derive(Default)]
+pub struct PostChain {
+    pub effects: Vec<PostEffect>,
+    scratch: Vec<Color>,
+}
+
+impl PostChain {
+    pub fn push(&mut self, effect: Box<dyn PostProcess>, enabled: bool) {
+        self.effects.push(PostEffect { enabled, effect });
+    }
+
+    // Formato: un efecto por línea, `nombre clave=valor ...`, y `#` para comentarios.
+    // `enabled=false` deja el efecto cargado pero apagado.
+    pub fn parse(text: &str, source: &str) -> Result<Self, String> {
+        let mut chain = PostChain::default();
+
+        for (number, line) in text.lines().enumerate() {
+            let error = |message: String| format!("{}:{}: {}", source, number + 1, message);
+            let line = line.split('#').next().unwrap_or("").trim();
+            let mut words = line.split_whitespace();
+            let Some(name) = words.next() else {
+                continue;
+            };
+
+            let mut effect = create_effect(name).ok_or_else(|| error(format!("Unknown effect '{}'", name)))?;
+            let mut enabled = true;
+
+            for word in words {
+                let (key, value) = word
+                    .split_once('=')
+                    .ok_or_else(|| error(format!("Expected key=value, found '{}'", word)))?;
+
+                if key == "enabled" {
+                    enabled = value
+                        .parse()
+                        .map_err(|_| error(format!("Invalid value for enabled: {}", value)))?;
+                    continue;
+                }
+
+                let value = value
+                    .parse()
+                    .map_err(|_| error(format!("Invalid value for {}: {}", key, value)))?;
+                effect.set_parameter(key, value).map_err(error)?;
+            }
+
+            chain.push(effect, enabled);
+        }
+
+        Ok(chain)
+    }
+
+    pub fn load(path: &str) -> Result<Self, String> {
+        let text = fs::read_to_string(path).map_err(|err| format!("{}: {}", path, err))?;
+        PostChain::parse(&text, path)
+    }
+
+    // Devuelve el nombre y el nuevo estado, o None si no hay efecto en esa posición
+    pub fn toggle(&mut self, index: usize) -> Option<(&'static str, bool)> {
+        let entry = self.effects.get_mut(index)?;
+        entry.enabled = !entry.enabled;
+        Some((entry.effect.name(), entry.enabled))
+    }
+
+    // Se aplica después de `resolve` y del bloom
+    pub fn run(&mut self, framebuffer: &mut Framebuffer, time: f32) {
+        self.scratch.resize(framebuffer.hdr_buffer.len(), Color::black());
+
+        for entry in self.effects.iter().filter(|entry| entry.enabled) {
+            let context = PostContext {
+                width: framebuffer.width,
+                height: framebuffer.height,
+                depth: &framebuffer.zbuffer,
+                time,
+            };
+            entry.effect.apply(&framebuffer.hdr_buffer, &mut self.scratch, &context);
+            std::mem::swap(&mut framebuffer.hdr_buffer, &mut self.scratch);
+        }
+    }
+}
@@ -0,0 +1,209 @@
+use nalgebra_glm::{Vec3, Mat4};
+use fastnoise_lite::FastNoiseLite;
+use crate::culling::RasterState;
+use crate::debug::{DebugMode, DebugShader};
+use crate::light::{Light, LightKind};
+use crate::orbit::Orbit;
+use crate::framebuffer::Framebuffer;
+use crate::shaders::{Shader, ShaderRegistry};
+use crate::tiles::RenderStats;
+use crate::vertex::Vertex;
+use crate::{create_model_matrix, render, Uniforms};
+
+// Transformación local respecto al nodo padre
+#[derive(Debug, Clone, Copy)]
+pub struct Transform {
+    pub translation: Vec3,
+    pub rotation: Vec3,
+    pub scale: f32,
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Transform {
+            translation: Vec3::zeros(),
+            rotation: Vec3::zeros(),
+            scale: 1.0,
+        }
+    }
+}
+
+// Parte de una malla con su propio shader, como las submallas por material de
+// un OBJ; sin shader se dibuja con el del nodo
+pub struct MeshPart {
+    pub vertices: Vec<Vertex>,
+    pub shader: Option<String>,
+}
+
+// Un nodo sin malla sirve de pivote: por ejemplo, la órbita de una luna gira
+// con su planeta sin heredar la escala del cuerpo
+pub struct Node {
+    pub name: String,
+    pub parent: Option<usize>,
+    pub transform: Transform,
+    // Velocidad angular en radianes por segundo, sumada a `transform.rotation`
+    pub spin: Vec3,
+    pub mesh: Option<usize>,
+    pub shader: String,
+    // La órbita se suma a `transform.translation`, relativa al padre
+    pub orbit: Option<Orbit>,
+    // Luz que acompaña al nodo; su posición se toma del origen del nodo en el mundo
+    pub light: Option<Light>,
+}
+
+impl Node {
+    pub fn new(name: &str) -> Self {
+        Node {
+            name: name.to_string(),
+            parent: None,
+            transform: Transform::default(),
+            spin: Vec3::zeros(),
+            mesh: None,
+            shader: String::new(),
+            orbit: None,
+            light: None,
+        }
+    }
+
+    fn local_matrix(&self, time: f32) -> Mat4 {
+        let rotation = self.transform.rotation + self.spin * time;
+        let mut translation = self.transform.translation;
+        if let Some(orbit) = &self.orbit {
+            translation += orbit.position(time);
+        }
+        create_model_matrix(translation, self.transform.scale, rotation)
+    }
+}
+
+// Los nodos se guardan en orden: un padre siempre va antes que sus hijos, así
+// las matrices de mundo se calculan en una sola pasada
+#[derive(Default)]
+pub struct Scene {
+    pub nodes: Vec<Node>,
+    pub meshes: Vec<Vec<MeshPart>>,
+    // Reloj de órbitas y rotaciones, aparte de `Uniforms::time`, que anima los
+    // shaders; así se puede acelerar el movimiento sin acelerar la superficie
+    pub time: f32,
+}
+
+impl Scene {
+    // Escena original: una sola estrella girando en el origen
+    pub fn single_body(mesh: Vec<MeshPart>, shader: &str, scale: f32, spin: Vec3) -> Self {
+        let mut scene = Scene::default();
+        let mesh = scene.add_mesh_parts(mesh);
+        let mut node = Node::new("star");
+        node.transform.scale = scale;
+        node.spin = spin;
+        node.mesh = Some(mesh);
+        node.shader = shader.to_string();
+        scene.add_node(node);
+        scene
+    }
+
+    pub fn add_mesh(&mut self, vertices: Vec<Vertex>) -> usize {
+        self.add_mesh_parts(vec![MeshPart { vertices, shader: None }])
+    }
+
+    pub fn add_mesh_parts(&mut self, parts: Vec<MeshPart>) -> usize {
+        self.meshes.push(parts);
+        self.meshes.len() - 1
+    }
+
+    pub fn add_node(&mut self, node: Node) -> usize {
+        if let Some(parent) = node.parent {
+            assert!(parent < self.nodes.len(), "Parent of node '{}' must be added first", node.name);
+        }
+        self.nodes.push(node);
+        self.nodes.len() - 1
+    }
+
+    // Primer nodo con malla; es el que cambia de shader con TAB
+    pub fn first_body(&self) -> Option<usize> {
+        self.nodes.iter().position(|node| node.mesh.is_some())
+    }
+
+    pub fn world_matrices(&self, time: f32) -> Vec<Mat4> {
+        let mut matrices: Vec<Mat4> = Vec::with_capacity(self.nodes.len());
+        for node in &self.nodes {
+            let local = node.local_matrix(time);
+            let world = match node.parent {
+                Some(parent) => matrices[parent] * local,
+                None => local,
+            };
+            matrices.push(world);
+        }
+        matrices
+    }
+
+    // Luces de los nodos, ya colocadas en el mundo con las matrices de `world_matrices`
+    pub fn lights(&self, matrices: &[Mat4]) -> Vec<Light> {
+        self.nodes
+            .iter()
+            .zip(matrices)
+            .filter_map(|(node, world)| {
+                let mut light = node.light?;
+                let origin = world.column(3).xyz();
+                match &mut light.kind {
+                    LightKind::Point { position, .. } | LightKind::Spot { position, .. } => *position = origin,
+                    LightKind::Directional { .. } => {}
+                }
+                Some(light)
+            })
+            .collect()
+    }
+}
+
+// Recorre la escena y dibuja cada parte de cada malla con la matriz de mundo del
+// nodo y el shader de la parte o, si no tiene, el del nodo.
+// Las luces de la escena se agregan a las de `uniforms`; sin ninguna se usa la luz por defecto.
+pub fn render_scene(
+    framebuffer: &mut Framebuffer,
+    scene: &Scene,
+    uniforms: &mut Uniforms,
+    shaders: &ShaderRegistry,
+    noise: &FastNoiseLite,
+    raster_state: RasterState,
+    debug_mode: DebugMode,
+) -> RenderStats {
+    let mut stats = RenderStats::default();
+
+    let matrices = scene.world_matrices(scene.time);
+    uniforms.lights.extend(scene.lights(&matrices));
+    if uniforms.lights.is_empty() {
+        uniforms.lights.push(Light::default());
+    }
+
+    // Primero los nodos opacos en orden; los translúcidos se mezclan con lo que ya
+    // está dibujado, así que van al final, del más lejano al más cercano
+    let mut transparent = Vec::new();
+    for (index, node) in scene.nodes.iter().enumerate() {
+        let Some(mesh) = node.mesh else {
+            continue;
+        };
+        for part in &scene.meshes[mesh] {
+            let name = part.shader.as_deref().unwrap_or(&node.shader);
+            let shader = shaders.get(name).expect("Unknown shader");
+            // Con cualquier vista de depuración todo pasa por `DebugShader`, que se dibuja
+            // opaco: así la profundidad y el overdraw también incluyen los translúcidos
+            if shader.is_transparent() && debug_mode == DebugMode::Off {
+                let distance = (matrices[index].column(3).xyz() - uniforms.camera_position).magnitude();
+                transparent.push((distance, index, &part.vertices, shader));
+                continue;
+            }
+
+            let debug_shader = DebugShader { mode: debug_mode, inner: shader };
+            let shader: &dyn Shader = if debug_mode == DebugMode::Off { shader } else { &debug_shader };
+
+            uniforms.model_matrix = matrices[index];
+            stats.add(&render(framebuffer, uniforms, &part.vertices, shader, noise, raster_state));
+        }
+    }
+
+    transparent.sort_by(|a, b| b.0.total_cmp(&a.0));
+    for (_, index, vertices, shader) in transparent {
+        uniforms.model_matrix = matrices[index];
+        stats.add(&render(framebuffer, uniforms, vertices, shader, noise, raster_state));
+    }
+
+    stats
+}
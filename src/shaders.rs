@@ -1,45 +1,111 @@
 use nalgebra_glm::{Vec3, Vec4, Mat3, mat4_to_mat3};
+use std::collections::HashMap;
+use std::io;
 use crate::vertex::Vertex;
 use crate::Uniforms;
 use crate::fragment::Fragment;
 use crate::color::Color;
+use crate::light::{blinn_phong, lambert, Material};
+use crate::obj::MtlMaterial;
+use crate::texture::{Sampler, Texture};
+use crate::celestial::{
+    GasGiantParams, GasGiantShader, IcyParams, IcyShader, LavaParams, LavaShader,
+    OceanParams, OceanShader, RingParams, RingShader, RockyParams, RockyShader,
+};
 use fastnoise_lite::FastNoiseLite;
 
-pub fn vertex_shader(vertex: &Vertex, uniforms: &Uniforms, noise: &FastNoiseLite) -> Vertex {
-    let position = Vec4::new(
-        vertex.position.x,
-        vertex.position.y,
-        vertex.position.z,
-        1.0
-    );
+pub trait Shader: Send + Sync {
+    fn vertex_shader(&self, vertex: &Vertex, uniforms: &Uniforms, _noise: &FastNoiseLite) -> Vertex {
+        transform_vertex(vertex, vertex.position, uniforms)
+    }
 
-    let noise_value = noise.get_noise_3d(
-        vertex.position.x * 1.5,
-        vertex.position.y * 1.5,
-        vertex.position.z * 1.5 + uniforms.time * 0.3
-    );
+    fn fragment_shader(&self, fragment: &Fragment, uniforms: &Uniforms, noise: &FastNoiseLite) -> Color;
 
-    let pulse = (uniforms.time * 1.8).sin() * 0.35 + 0.65;
-    let displacement = noise_value * 0.08 * pulse;
-    
-    let displaced_position = Vec4::new(
-        position.x + vertex.normal.x * displacement,
-        position.y + vertex.normal.y * displacement,
-        position.z + vertex.normal.z * displacement,
-        1.0
-    );
+    // Los shaders que cambian la profundidad deben devolver true: así el
+    // pipeline no descarta fragmentos antes de sombrearlos
+    fn modifies_depth(&self) -> bool {
+        false
+    }
 
-    let transformed = uniforms.projection_matrix * uniforms.view_matrix * uniforms.model_matrix * displaced_position;
+    fn fragment_depth(&self, fragment: &Fragment, _uniforms: &Uniforms, _noise: &FastNoiseLite) -> f32 {
+        fragment.depth
+    }
 
-    let w = transformed.w;
-    let ndc_position = Vec4::new(
-        transformed.x / w,
-        transformed.y / w,
-        transformed.z / w,
+    // Los shaders translúcidos devuelven true y su opacidad en `fragment_alpha`:
+    // se dibujan después de los opacos, sin escribir profundidad
+    fn is_transparent(&self) -> bool {
+        false
+    }
+
+    fn fragment_alpha(&self, _fragment: &Fragment, _uniforms: &Uniforms, _noise: &FastNoiseLite) -> f32 {
         1.0
-    );
+    }
+
+    // Sin culling y con la normal invertida en las caras traseras, para
+    // superficies sin grosor como los anillos
+    fn double_sided(&self) -> bool {
+        false
+    }
+}
+
+// Registro de shaders por nombre para elegir uno por objeto en tiempo de ejecución
+pub struct ShaderRegistry {
+    shaders: HashMap<String, Box<dyn Shader>>,
+    order: Vec<String>,
+}
 
-    let screen_position = uniforms.viewport_matrix * ndc_position;
+impl ShaderRegistry {
+    pub fn new() -> Self {
+        ShaderRegistry {
+            shaders: HashMap::new(),
+            order: Vec::new(),
+        }
+    }
+
+    pub fn with_defaults() -> Self {
+        let mut registry = ShaderRegistry::new();
+        registry.register("star", StarShader);
+        registry.register("flat", FlatShader);
+        registry.register("phong", PhongShader {
+            material: Material { albedo: Color::from_float(1.0, 1.0, 1.0), ambient: 0.05, specular: 0.5, shininess: 48.0 },
+        });
+        registry.register("rocky", RockyShader::new(RockyParams::default()));
+        registry.register("gas_giant", GasGiantShader { params: GasGiantParams::default() });
+        registry.register("icy", IcyShader::new(IcyParams::default()));
+        registry.register("lava", LavaShader { params: LavaParams::default() });
+        registry.register("ocean", OceanShader { params: OceanParams::default() });
+        registry.register("ring", RingShader { params: RingParams::default() });
+        registry
+    }
+
+    pub fn register<S: Shader + 'static>(&mut self, name: &str, shader: S) {
+        if self.shaders.insert(name.to_string(), Box::new(shader)).is_none() {
+            self.order.push(name.to_string());
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&dyn Shader> {
+        self.shaders.get(name).map(|shader| shader.as_ref())
+    }
+
+    pub fn names(&self) -> &[String] {
+        &self.order
+    }
+
+    // Nombre del shader que sigue a `current`, en orden de registro
+    pub fn next_name(&self, current: &str) -> Option<&str> {
+        let index = self.order.iter().position(|name| name == current)?;
+        self.order.get((index + 1) % self.order.len()).map(|name| name.as_str())
+    }
+}
+
+// Lleva el vértice a espacio de recorte; la división por w y el viewport
+// se aplican después de recortar
+pub fn transform_vertex(vertex: &Vertex, object_position: Vec3, uniforms: &Uniforms) -> Vertex {
+    let position = Vec4::new(object_position.x, object_position.y, object_position.z, 1.0);
+
+    let world_position = uniforms.model_matrix * position;
+    let clip_position = uniforms.projection_matrix * uniforms.view_matrix * world_position;
 
     let model_mat3 = mat4_to_mat3(&uniforms.model_matrix);
     let normal_matrix = model_mat3.transpose().try_inverse().unwrap_or(Mat3::identity());
@@ -50,12 +116,127 @@ pub fn vertex_shader(vertex: &Vertex, uniforms: &Uniforms, noise: &FastNoiseLite
         normal: vertex.normal,
         tex_coords: vertex.tex_coords,
         color: vertex.color,
-        transformed_position: Vec3::new(screen_position.x, screen_position.y, screen_position.z),
+        clip_position,
+        world_position: world_position.xyz(),
+        transformed_position: Vec3::zeros(),
         transformed_normal,
     }
 }
 
-pub fn fragment_shader(fragment: &Fragment, uniforms: &Uniforms, noise: &FastNoiseLite) -> Color {
+// Estrella tipo G con superficie animada por ruido
+pub struct StarShader;
+
+impl Shader for StarShader {
+    fn vertex_shader(&self, vertex: &Vertex, uniforms: &Uniforms, noise: &FastNoiseLite) -> Vertex {
+        let noise_value = noise.get_noise_3d(
+            vertex.position.x * 1.5,
+            vertex.position.y * 1.5,
+            vertex.position.z * 1.5 + uniforms.time * 0.3
+        );
+
+        let pulse = (uniforms.time * 1.8).sin() * 0.35 + 0.65;
+        let displacement = noise_value * 0.08 * pulse;
+
+        transform_vertex(vertex, vertex.position + vertex.normal * displacement, uniforms)
+    }
+
+    fn fragment_shader(&self, fragment: &Fragment, uniforms: &Uniforms, noise: &FastNoiseLite) -> Color {
+        star_fragment_shader(fragment, uniforms, noise)
+    }
+}
+
+// Color del vértice con iluminación difusa simple, útil para comparar
+pub struct FlatShader;
+
+impl Shader for FlatShader {
+    fn fragment_shader(&self, fragment: &Fragment, uniforms: &Uniforms, _noise: &FastNoiseLite) -> Color {
+        let diffuse = lambert(&uniforms.lights, &fragment.world_position, &fragment.normal, fragment.color * 0.8);
+        fragment.color * 0.2 + diffuse
+    }
+}
+
+// Superficie no emisiva iluminada por las luces de la escena
+pub struct PhongShader {
+    pub material: Material,
+}
+
+impl Shader for PhongShader {
+    fn fragment_shader(&self, fragment: &Fragment, uniforms: &Uniforms, _noise: &FastNoiseLite) -> Color {
+        let material = Material { albedo: self.material.albedo * fragment.color, ..self.material };
+        blinn_phong(&uniforms.lights, &fragment.world_position, &fragment.normal, &uniforms.camera_position, &material)
+    }
+}
+
+// Textura de artista modulada por el ruido procedural e iluminada por la escena
+pub struct TexturedShader {
+    pub texture: Texture,
+    pub sampler: Sampler,
+    pub material: Material,
+    // Cuánto oscurece o aclara el ruido a la textura (0 = textura pura)
+    pub noise_detail: f32,
+}
+
+impl Shader for TexturedShader {
+    fn fragment_shader(&self, fragment: &Fragment, uniforms: &Uniforms, noise: &FastNoiseLite) -> Color {
+        let pos = fragment.vertex_position * 6.0;
+        let detail = noise.get_noise_3d(pos.x, pos.y, pos.z);
+        let texel = self.texture.sample(&self.sampler, fragment.tex_coords, fragment.uv_dx, fragment.uv_dy);
+        let albedo = texel * (1.0 + detail * self.noise_detail);
+
+        let material = Material { albedo, ..self.material };
+        blinn_phong(&uniforms.lights, &fragment.world_position, &fragment.normal, &uniforms.camera_position, &material)
+    }
+}
+
+// Material de un archivo MTL: Kd (por `map_Kd` si hay textura), brillo de Ks y
+// Ns, emisión Ke y opacidad d. Ks se reduce a su promedio.
+pub struct MaterialShader {
+    pub material: Material,
+    pub emission: Color,
+    pub opacity: f32,
+    pub texture: Option<Texture>,
+    pub sampler: Sampler,
+}
+
+impl MaterialShader {
+    pub fn from_mtl(mtl: &MtlMaterial, sampler: Sampler) -> io::Result<Self> {
+        let texture = mtl.diffuse_map.as_deref().map(Texture::load).transpose()?;
+        Ok(MaterialShader {
+            material: Material {
+                albedo: Color::from_float(mtl.diffuse.x, mtl.diffuse.y, mtl.diffuse.z),
+                ambient: 0.05,
+                specular: (mtl.specular.x + mtl.specular.y + mtl.specular.z) / 3.0,
+                shininess: mtl.shininess.max(1.0),
+            },
+            emission: Color::from_float(mtl.emission.x, mtl.emission.y, mtl.emission.z),
+            opacity: mtl.opacity.clamp(0.0, 1.0),
+            texture,
+            sampler,
+        })
+    }
+}
+
+impl Shader for MaterialShader {
+    fn fragment_shader(&self, fragment: &Fragment, uniforms: &Uniforms, _noise: &FastNoiseLite) -> Color {
+        let mut albedo = self.material.albedo * fragment.color;
+        if let Some(texture) = &self.texture {
+            albedo = albedo * texture.sample(&self.sampler, fragment.tex_coords, fragment.uv_dx, fragment.uv_dy);
+        }
+
+        let material = Material { albedo, ..self.material };
+        blinn_phong(&uniforms.lights, &fragment.world_position, &fragment.normal, &uniforms.camera_position, &material) + self.emission
+    }
+
+    fn is_transparent(&self) -> bool {
+        self.opacity < 1.0
+    }
+
+    fn fragment_alpha(&self, _fragment: &Fragment, _uniforms: &Uniforms, _noise: &FastNoiseLite) -> f32 {
+        self.opacity
+    }
+}
+
+fn star_fragment_shader(fragment: &Fragment, uniforms: &Uniforms, noise: &FastNoiseLite) -> Color {
     let pos = fragment.vertex_position;
     let time_factor = uniforms.time * 0.18;
 
@@ -131,7 +312,9 @@ pub fn fragment_shader(fragment: &Fragment, uniforms: &Uniforms, noise: &FastNoi
     // Efecto de corona en los bordes
     let view_dir = fragment.vertex_position.normalize();
     let normal = fragment.normal.normalize();
-    let fresnel = (1.0 - view_dir.dot(&normal).abs()).powf(3.2);
+    // Aun normalizados, |dot| puede pasar de 1 por redondeo y powf de una base
+    // negativa da NaN
+    let fresnel = (1.0 - view_dir.dot(&normal).abs()).max(0.0).powf(3.2);
     let edge_glow = fresnel * 0.3 * pulse1;
     
     color * (pulsating_emission + edge_glow)
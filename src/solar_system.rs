@@ -0,0 +1,188 @@
+use std::collections::HashMap;
+use std::fs;
+use nalgebra_glm::Vec3;
+use crate::camera::Camera;
+use crate::color::Color;
+use crate::light::{parse_vec3, Light, LightKind};
+use crate::mesh::annulus;
+use crate::orbit::Orbit;
+use crate::scene::{MeshPart, Node, Scene};
+use crate::shaders::ShaderRegistry;
+
+pub struct SolarSystem {
+    pub scene: Scene,
+    pub camera: Camera,
+}
+
+// Formato: `body nombre clave=valor ...` por cuerpo y `camera eye=X,Y,Z`; `#` para
+// comentarios. Un cuerpo con `parent` orbita alrededor de ese cuerpo, que debe
+// aparecer antes. Los ángulos van en grados, `period` en segundos por vuelta y
+// `spin` en radianes por segundo.
+//
+// Cada cuerpo son dos nodos: un pivote con la órbita, del que cuelgan los
+// satélites, y el cuerpo visible con la escala, la inclinación del eje y el giro.
+// `ring=INTERIOR,EXTERIOR` agrega un anillo hijo del cuerpo, con los radios en
+// radios del cuerpo, que comparte su inclinación.
+pub fn parse(text: &str, source: &str, mesh: Vec<MeshPart>, shaders: &ShaderRegistry) -> Result<SolarSystem, String> {
+    let mut scene = Scene::default();
+    let mesh = scene.add_mesh_parts(mesh);
+    let mut camera = Camera::new(Vec3::new(0.0, 45.0, 115.0), Vec3::zeros(), Vec3::new(0.0, 1.0, 0.0));
+    // Nombre del cuerpo -> índice de su pivote
+    let mut pivots: HashMap<String, usize> = HashMap::new();
+
+    for (number, line) in text.lines().enumerate() {
+        let error = |message: String| format!("{}:{}: {}", source, number + 1, message);
+        let line = line.split('#').next().unwrap_or("").trim();
+        let mut words = line.split_whitespace();
+        let Some(kind) = words.next() else {
+            continue;
+        };
+        let name = if kind == "body" {
+            words.next().filter(|word| !word.contains('=')).ok_or_else(|| error(String::from("Missing body name")))?
+        } else {
+            ""
+        };
+
+        let mut pairs = Vec::new();
+        for word in words {
+            let pair = word
+                .split_once('=')
+                .ok_or_else(|| error(format!("Expected key=value, found '{}'", word)))?;
+            pairs.push(pair);
+        }
+        let number_value = |key: &str, value: &str| {
+            value
+                .parse::<f32>()
+                .map_err(|_| error(format!("Invalid value for {}: {}", key, value)))
+        };
+
+        match kind {
+            "camera" => {
+                for (key, value) in pairs {
+                    match key {
+                        "eye" => camera.eye = parse_vec3(value).ok_or_else(|| error(format!("Invalid value for eye: {}", value)))?,
+                        _ => return Err(error(format!("Unknown camera parameter '{}'", key))),
+                    }
+                }
+            }
+            "body" => {
+                if pivots.contains_key(name) {
+                    return Err(error(format!("Duplicate body '{}'", name)));
+                }
+
+                let mut pivot = Node::new(&format!("{}_orbit", name));
+                let mut body = Node::new(name);
+                body.mesh = Some(mesh);
+                body.shader = String::from("flat");
+                let mut orbit = Orbit::default();
+                let mut has_orbit = false;
+                let mut tilt = 0.0;
+                let mut light: Option<Light> = None;
+                let mut ring = None;
+
+                for (key, value) in pairs {
+                    match key {
+                        "parent" => {
+                            let parent = pivots
+                                .get(value)
+                                .ok_or_else(|| error(format!("Unknown parent '{}' (it must be defined first)", value)))?;
+                            pivot.parent = Some(*parent);
+                        }
+                        "shader" => {
+                            if shaders.get(value).is_none() {
+                                return Err(error(format!("Unknown shader '{}'", value)));
+                            }
+                            body.shader = value.to_string();
+                        }
+                        "radius" => body.transform.scale = number_value(key, value)?,
+                        "spin" => body.spin = Vec3::new(0.0, number_value(key, value)?, 0.0),
+                        "tilt" => tilt = number_value(key, value)?.to_radians(),
+                        "ring" => {
+                            let invalid = || error(format!("Invalid value for ring: {} (use INNER,OUTER)", value));
+                            let (inner, outer) = value.split_once(',').ok_or_else(invalid)?;
+                            let inner: f32 = inner.parse().map_err(|_| invalid())?;
+                            let outer: f32 = outer.parse().map_err(|_| invalid())?;
+                            if inner <= 0.0 || outer <= inner {
+                                return Err(invalid());
+                            }
+                            ring = Some((inner, outer));
+                        }
+                        "a" => {
+                            orbit.semi_major_axis = number_value(key, value)?;
+                            has_orbit = true;
+                        }
+                        "e" => {
+                            orbit.eccentricity = number_value(key, value)?;
+                            if !(0.0..1.0).contains(&orbit.eccentricity) {
+                                return Err(error(format!("Eccentricity must be in [0, 1): {}", value)));
+                            }
+                        }
+                        "i" => orbit.inclination = number_value(key, value)?.to_radians(),
+                        "node" => orbit.ascending_node = number_value(key, value)?.to_radians(),
+                        "periapsis" => orbit.argument_of_periapsis = number_value(key, value)?.to_radians(),
+                        "anomaly" => orbit.mean_anomaly = number_value(key, value)?.to_radians(),
+                        "period" => {
+                            orbit.period = number_value(key, value)?;
+                            if orbit.period == 0.0 {
+                                return Err(error(String::from("Period cannot be zero")));
+                            }
+                        }
+                        // El cuerpo emite luz: `light=RRGGBB`, con `intensity` y `range` opcionales
+                        "light" => {
+                            let hex = u32::from_str_radix(value, 16)
+                                .map_err(|_| error(format!("Invalid value for light: {}", value)))?;
+                            let mut point = light.unwrap_or_else(|| Light::point(Vec3::zeros(), 500.0));
+                            point.color = Color::from_hex(hex);
+                            light = Some(point);
+                        }
+                        "intensity" => {
+                            let point = light.as_mut().ok_or_else(|| error(String::from("intensity requires light= first")))?;
+                            point.intensity = number_value(key, value)?;
+                        }
+                        "range" => {
+                            let point = light.as_mut().ok_or_else(|| error(String::from("range requires light= first")))?;
+                            if let LightKind::Point { range, .. } = &mut point.kind {
+                                *range = number_value(key, value)?;
+                            }
+                        }
+                        _ => return Err(error(format!("Unknown body parameter '{}'", key))),
+                    }
+                }
+
+                if has_orbit {
+                    if pivot.parent.is_none() {
+                        return Err(error(format!("Body '{}' has an orbit but no parent", name)));
+                    }
+                    pivot.orbit = Some(orbit);
+                }
+                pivot.light = light;
+                body.transform.rotation = Vec3::new(0.0, 0.0, tilt);
+
+                let pivot = scene.add_node(pivot);
+                body.parent = Some(pivot);
+                let body = scene.add_node(body);
+                pivots.insert(name.to_string(), pivot);
+
+                if let Some((inner, outer)) = ring {
+                    let mut node = Node::new(&format!("{}_ring", name));
+                    node.parent = Some(body);
+                    node.mesh = Some(scene.add_mesh(annulus(inner, outer, 128)));
+                    node.shader = String::from("ring");
+                    scene.add_node(node);
+                }
+            }
+            _ => return Err(error(format!("Unknown entry '{}' (use body or camera)", kind))),
+        }
+    }
+
+    if pivots.is_empty() {
+        return Err(format!("{}: no bodies defined", source));
+    }
+
+    Ok(SolarSystem { scene, camera })
+}
+
+pub fn load(path: &str, mesh: Vec<MeshPart>, shaders: &ShaderRegistry) -> Result<SolarSystem, String> {
+    let text = fs::read_to_string(path).map_err(|err| format!("{}: {}", path, err))?;
+    parse(&text, path, mesh, shaders)
+}
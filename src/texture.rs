@@ -0,0 +1,202 @@
+use std::io;
+use std::str::FromStr;
+use nalgebra_glm::Vec2;
+use crate::color::Color;
+use crate::image_io::load_image;
+
+// Nearest y Bilinear leen solo el nivel base; los demás usan los mipmaps
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Filter {
+    Nearest,
+    Bilinear,
+    // Bilineal en los dos niveles más cercanos y mezcla entre ellos
+    Trilinear,
+    // Varias muestras trilineales a lo largo del eje mayor de la huella del píxel
+    Anisotropic(usize),
+}
+
+// Qué hacer con coordenadas fuera de [0, 1]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Wrap {
+    Repeat,
+    Clamp,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Sampler {
+    pub filter: Filter,
+    pub wrap: Wrap,
+}
+
+impl Default for Sampler {
+    fn default() -> Self {
+        Sampler {
+            filter: Filter::Bilinear,
+            wrap: Wrap::Repeat,
+        }
+    }
+}
+
+// Imagen en color lineal; v = 0 es la fila de abajo, como en los `vt` de OBJ.
+// `levels[0]` es la imagen original y cada nivel siguiente mide la mitad.
+pub struct Texture {
+    pub width: usize,
+    pub height: usize,
+    levels: Vec<MipLevel>,
+}
+
+struct MipLevel {
+    width: usize,
+    height: usize,
+    texels: Vec<Color>,
+}
+
+impl MipLevel {
+    fn texel(&self, x: isize, y: isize, wrap: Wrap) -> Color {
+        let (x, y) = match wrap {
+            Wrap::Repeat => (x.rem_euclid(self.width as isize), y.rem_euclid(self.height as isize)),
+            Wrap::Clamp => (x.clamp(0, self.width as isize - 1), y.clamp(0, self.height as isize - 1)),
+        };
+        self.texels[y as usize * self.width + x as usize]
+    }
+
+    // Promedio de bloques de 2x2 en espacio lineal; en tamaños impares se repite el borde
+    fn downsample(&self) -> MipLevel {
+        let width = (self.width / 2).max(1);
+        let height = (self.height / 2).max(1);
+        let mut texels = Vec::with_capacity(width * height);
+
+        for y in 0..height as isize {
+            for x in 0..width as isize {
+                let sum = self.texel(x * 2, y * 2, Wrap::Clamp)
+                    + self.texel(x * 2 + 1, y * 2, Wrap::Clamp)
+                    + self.texel(x * 2, y * 2 + 1, Wrap::Clamp)
+                    + self.texel(x * 2 + 1, y * 2 + 1, Wrap::Clamp);
+                texels.push(sum * 0.25);
+            }
+        }
+
+        MipLevel { width, height, texels }
+    }
+
+    fn nearest(&self, uv: Vec2, wrap: Wrap) -> Color {
+        let x = uv.x * self.width as f32;
+        let y = (1.0 - uv.y) * self.height as f32;
+        self.texel(x.floor() as isize, y.floor() as isize, wrap)
+    }
+
+    fn bilinear(&self, uv: Vec2, wrap: Wrap) -> Color {
+        // Coordenadas en texeles, con el centro del texel en .5
+        let x = uv.x * self.width as f32 - 0.5;
+        let y = (1.0 - uv.y) * self.height as f32 - 0.5;
+        let (x0, y0) = (x.floor(), y.floor());
+        let (tx, ty) = (x - x0, y - y0);
+        let (x0, y0) = (x0 as isize, y0 as isize);
+
+        let top = self.texel(x0, y0, wrap) * (1.0 - tx) + self.texel(x0 + 1, y0, wrap) * tx;
+        let bottom = self.texel(x0, y0 + 1, wrap) * (1.0 - tx) + self.texel(x0 + 1, y0 + 1, wrap) * tx;
+        top * (1.0 - ty) + bottom * ty
+    }
+}
+
+impl Texture {
+    pub fn load(path: &str) -> io::Result<Self> {
+        let image = load_image(path)?;
+        if image.width == 0 || image.height == 0 {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, format!("{}: empty image", path)));
+        }
+
+        // Las imágenes se guardan en sRGB
+        let texels = image.pixels.iter().map(|&[r, g, b]| Color::new(r, g, b)).collect();
+        let mut levels = vec![MipLevel { width: image.width, height: image.height, texels }];
+        while let Some(last) = levels.last().filter(|level| level.width > 1 || level.height > 1) {
+            levels.push(last.downsample());
+        }
+
+        Ok(Texture { width: image.width, height: image.height, levels })
+    }
+
+    pub fn mip_levels(&self) -> usize {
+        self.levels.len()
+    }
+
+    // Trilineal en un nivel fraccionario
+    fn trilinear(&self, uv: Vec2, lod: f32, wrap: Wrap) -> Color {
+        let lod = lod.clamp(0.0, (self.levels.len() - 1) as f32);
+        let base = lod.floor() as usize;
+        let fine = self.levels[base].bilinear(uv, wrap);
+        if base + 1 == self.levels.len() {
+            return fine;
+        }
+
+        let t = lod - base as f32;
+        fine * (1.0 - t) + self.levels[base + 1].bilinear(uv, wrap) * t
+    }
+
+    // `uv_dx` y `uv_dy` son las derivadas de las UV por píxel (ver `Fragment`);
+    // definen la huella del píxel sobre la textura y con ella el nivel de mipmap
+    pub fn sample(&self, sampler: &Sampler, uv: Vec2, uv_dx: Vec2, uv_dy: Vec2) -> Color {
+        let base = &self.levels[0];
+        let size = Vec2::new(self.width as f32, self.height as f32);
+        let axis_x = uv_dx.component_mul(&size);
+        let axis_y = uv_dy.component_mul(&size);
+        let length_x = axis_x.magnitude();
+        let length_y = axis_y.magnitude();
+
+        match sampler.filter {
+            Filter::Nearest => base.nearest(uv, sampler.wrap),
+            Filter::Bilinear => base.bilinear(uv, sampler.wrap),
+            Filter::Trilinear => {
+                let lod = length_x.max(length_y).max(1e-8).log2();
+                self.trilinear(uv, lod, sampler.wrap)
+            }
+            Filter::Anisotropic(max_samples) => {
+                let (major, major_length, minor_length) = if length_x >= length_y {
+                    (uv_dx, length_x, length_y)
+                } else {
+                    (uv_dy, length_y, length_x)
+                };
+
+                // El nivel se elige con el eje menor, limitado para no pasar de `max_samples`
+                let samples = (major_length / minor_length.max(1e-8)).ceil().clamp(1.0, max_samples.max(1) as f32);
+                let lod = (major_length / samples).max(minor_length).max(1e-8).log2();
+
+                let count = samples as usize;
+                let mut sum = Color::black();
+                for i in 0..count {
+                    let offset = (i as f32 + 0.5) / samples - 0.5;
+                    sum = sum + self.trilinear(uv + major * offset, lod, sampler.wrap);
+                }
+                sum * (1.0 / samples)
+            }
+        }
+    }
+}
+
+impl FromStr for Filter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "nearest" => Ok(Filter::Nearest),
+            "bilinear" => Ok(Filter::Bilinear),
+            "trilinear" => Ok(Filter::Trilinear),
+            _ => match s.strip_prefix("aniso").map(str::parse) {
+                Some(Ok(samples @ (2 | 4 | 8 | 16))) => Ok(Filter::Anisotropic(samples)),
+                _ => Err(format!("Unknown texture filter: {} (use nearest, bilinear, trilinear or aniso2/4/8/16)", s)),
+            },
+        }
+    }
+}
+
+impl FromStr for Wrap {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "repeat" => Ok(Wrap::Repeat),
+            "clamp" => Ok(Wrap::Clamp),
+            _ => Err(format!("Unknown texture wrap mode: {}", s)),
+        }
+    }
+}
@@ -0,0 +1,274 @@
+use rayon::prelude::*;
+use crate::color::Color;
+use crate::fragment::Fragment;
+use crate::framebuffer::{Framebuffer, MAX_SAMPLES};
+use crate::triangle::{bounding_box, triangle, Rect};
+use crate::vertex::Vertex;
+
+pub const TILE_SIZE: usize = 64;
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RenderStats {
+    pub fragments: usize,
+    pub shaded: usize,
+    pub early_z_rejected: usize,
+    pub culled_triangles: usize,
+}
+
+impl RenderStats {
+    pub fn add(&mut self, other: &RenderStats) {
+        self.fragments += other.fragments;
+        self.shaded += other.shaded;
+        self.early_z_rejected += other.early_z_rejected;
+        self.culled_triangles += other.culled_triangles;
+    }
+}
+
+// Región del framebuffer con su propia copia de las muestras de color y
+// profundidad, para que cada hilo escriba sin compartir memoria
+pub struct Tile {
+    pub rect: Rect,
+    width: usize,
+    sample_count: usize,
+    color_samples: Vec<Color>,
+    depth_samples: Vec<f32>,
+    // Se acumula desde cero y se suma al del framebuffer al guardar
+    overdraw: Vec<u32>,
+    stats: RenderStats,
+}
+
+impl Tile {
+    fn load(framebuffer: &Framebuffer, rect: Rect) -> Self {
+        let width = (rect.max_x - rect.min_x + 1) as usize;
+        let height = (rect.max_y - rect.min_y + 1) as usize;
+        let sample_count = framebuffer.sample_count;
+        let row_len = width * sample_count;
+        let mut color_samples = Vec::with_capacity(row_len * height);
+        let mut depth_samples = Vec::with_capacity(row_len * height);
+
+        for y in rect.min_y as usize..=rect.max_y as usize {
+            let row = (y * framebuffer.raster_width + rect.min_x as usize) * sample_count;
+            color_samples.extend_from_slice(&framebuffer.color_samples[row..row + row_len]);
+            depth_samples.extend_from_slice(&framebuffer.depth_samples[row..row + row_len]);
+        }
+
+        Tile {
+            rect,
+            width,
+            sample_count,
+            color_samples,
+            depth_samples,
+            overdraw: vec![0; width * height],
+            stats: RenderStats::default(),
+        }
+    }
+
+    fn store(&self, framebuffer: &mut Framebuffer) {
+        let row_len = self.width * self.sample_count;
+        for (i, y) in (self.rect.min_y as usize..=self.rect.max_y as usize).enumerate() {
+            let row = (y * framebuffer.raster_width + self.rect.min_x as usize) * self.sample_count;
+            let local = i * row_len;
+            framebuffer.color_samples[row..row + row_len].copy_from_slice(&self.color_samples[local..local + row_len]);
+            framebuffer.depth_samples[row..row + row_len].copy_from_slice(&self.depth_samples[local..local + row_len]);
+
+            let pixels = y * framebuffer.raster_width + self.rect.min_x as usize;
+            let local_pixels = i * self.width;
+            for (stored, count) in framebuffer.overdraw[pixels..pixels + self.width]
+                .iter_mut()
+                .zip(&self.overdraw[local_pixels..local_pixels + self.width])
+            {
+                *stored += count;
+            }
+        }
+    }
+
+    fn index(&self, x: usize, y: usize) -> usize {
+        ((y - self.rect.min_y as usize) * self.width + (x - self.rect.min_x as usize)) * self.sample_count
+    }
+
+    // Máscara de las muestras cubiertas que pasan la prueba de profundidad
+    fn depth_test(&self, x: usize, y: usize, coverage: u32, depths: &[f32; MAX_SAMPLES]) -> u32 {
+        let index = self.index(x, y);
+        let stored = &self.depth_samples[index..index + self.sample_count];
+        let mut passed = 0;
+        for (sample, (depth, stored)) in depths.iter().zip(stored).enumerate() {
+            if coverage & (1 << sample) != 0 && depth < stored {
+                passed |= 1 << sample;
+            }
+        }
+        passed
+    }
+
+    fn count_shaded(&mut self, x: usize, y: usize) {
+        let index = (y - self.rect.min_y as usize) * self.width + (x - self.rect.min_x as usize);
+        self.overdraw[index] += 1;
+        self.stats.shaded += 1;
+    }
+
+    // Mezcla "over" sobre las muestras de `mask`; la profundidad no se escribe,
+    // así lo que está detrás de una superficie translúcida sigue visible
+    fn blend(&mut self, x: usize, y: usize, mask: u32, color: Color, alpha: f32) {
+        let index = self.index(x, y);
+        for (sample, stored) in self.color_samples[index..index + self.sample_count].iter_mut().enumerate() {
+            if mask & (1 << sample) != 0 {
+                *stored = color * alpha + *stored * (1.0 - alpha);
+            }
+        }
+    }
+
+    fn write(&mut self, x: usize, y: usize, mask: u32, depths: &[f32; MAX_SAMPLES], color: Color) {
+        let range = self.index(x, y)..self.index(x, y) + self.sample_count;
+        let samples = self.color_samples[range.clone()].iter_mut().zip(&mut self.depth_samples[range]);
+        for (sample, ((stored_color, stored_depth), depth)) in samples.zip(depths).enumerate() {
+            if mask & (1 << sample) != 0 {
+                *stored_color = color;
+                *stored_depth = *depth;
+            }
+        }
+    }
+}
+
+pub struct TileGrid {
+    pub columns: usize,
+    pub rows: usize,
+    width: usize,
+    height: usize,
+}
+
+impl TileGrid {
+    pub fn new(width: usize, height: usize) -> Self {
+        TileGrid {
+            columns: width.div_ceil(TILE_SIZE),
+            rows: height.div_ceil(TILE_SIZE),
+            width,
+            height,
+        }
+    }
+
+    pub fn tile_rect(&self, column: usize, row: usize) -> Rect {
+        let min_x = column * TILE_SIZE;
+        let min_y = row * TILE_SIZE;
+        Rect {
+            min_x: min_x as i32,
+            min_y: min_y as i32,
+            max_x: ((min_x + TILE_SIZE).min(self.width) - 1) as i32,
+            max_y: ((min_y + TILE_SIZE).min(self.height) - 1) as i32,
+        }
+    }
+
+    fn screen_rect(&self) -> Rect {
+        Rect {
+            min_x: 0,
+            min_y: 0,
+            max_x: self.width as i32 - 1,
+            max_y: self.height as i32 - 1,
+        }
+    }
+
+    // Lista de triángulos (índice del primer vértice / 3) por tile, en orden de envío
+    pub fn bin_triangles(&self, vertices: &[Vertex]) -> Vec<Vec<usize>> {
+        let mut bins = vec![Vec::new(); self.columns * self.rows];
+        let screen = self.screen_rect();
+
+        for (index, tri) in vertices.chunks_exact(3).enumerate() {
+            let bounds = bounding_box(
+                &tri[0].transformed_position,
+                &tri[1].transformed_position,
+                &tri[2].transformed_position,
+            );
+            let Some(bounds) = bounds.intersect(&screen) else {
+                continue;
+            };
+
+            let first_column = bounds.min_x as usize / TILE_SIZE;
+            let last_column = bounds.max_x as usize / TILE_SIZE;
+            let first_row = bounds.min_y as usize / TILE_SIZE;
+            let last_row = bounds.max_y as usize / TILE_SIZE;
+
+            for row in first_row..=last_row {
+                for column in first_column..=last_column {
+                    bins[row * self.columns + column].push(index);
+                }
+            }
+        }
+
+        bins
+    }
+}
+
+// Rasteriza y sombrea cada tile en paralelo. Los triángulos se procesan en el
+// mismo orden dentro de cada tile, así que el resultado es idéntico al secuencial.
+// Con `early_depth_test` la profundidad se prueba antes de llamar a `shade`, que
+// devuelve el color, la opacidad y, si el shader la cambia, la nueva profundidad.
+// Con `blend` el color se mezcla según la opacidad y no se escribe profundidad.
+pub fn render_tiles<F>(
+    framebuffer: &mut Framebuffer,
+    vertices: &[Vertex],
+    early_depth_test: bool,
+    blend: bool,
+    shade: F,
+) -> RenderStats
+where
+    F: Fn(&Fragment) -> (Color, f32, Option<f32>) + Sync,
+{
+    let grid = TileGrid::new(framebuffer.raster_width, framebuffer.raster_height);
+    let bins = grid.bin_triangles(vertices);
+    let samples = framebuffer.sample_positions();
+
+    let mut work: Vec<(Tile, Vec<usize>)> = bins
+        .into_iter()
+        .enumerate()
+        .filter(|(_, bin)| !bin.is_empty())
+        .map(|(index, bin)| {
+            let rect = grid.tile_rect(index % grid.columns, index / grid.columns);
+            (Tile::load(framebuffer, rect), bin)
+        })
+        .collect();
+
+    work.par_iter_mut().for_each(|(tile, bin)| {
+        let rect = tile.rect;
+        for &index in bin.iter() {
+            let i = index * 3;
+            triangle(&vertices[i], &vertices[i + 1], &vertices[i + 2], &rect, samples, |fragment| {
+                let x = fragment.position.x as usize;
+                let y = fragment.position.y as usize;
+                tile.stats.fragments += 1;
+
+                if early_depth_test {
+                    let passed = tile.depth_test(x, y, fragment.coverage, &fragment.sample_depths);
+                    if passed == 0 {
+                        tile.stats.early_z_rejected += 1;
+                        return;
+                    }
+
+                    let (color, alpha, _) = shade(fragment);
+                    tile.count_shaded(x, y);
+                    if blend {
+                        tile.blend(x, y, passed, color, alpha);
+                    } else {
+                        tile.write(x, y, passed, &fragment.sample_depths, color);
+                    }
+                } else {
+                    let (color, alpha, depth) = shade(fragment);
+                    tile.count_shaded(x, y);
+
+                    let depths = depth.map_or(fragment.sample_depths, |depth| [depth; MAX_SAMPLES]);
+                    let passed = tile.depth_test(x, y, fragment.coverage, &depths);
+                    if blend {
+                        tile.blend(x, y, passed, color, alpha);
+                    } else {
+                        tile.write(x, y, passed, &depths, color);
+                    }
+                }
+            });
+        }
+    });
+
+    let mut stats = RenderStats::default();
+    for (tile, _) in &work {
+        tile.store(framebuffer);
+        stats.add(&tile.stats);
+    }
+
+    stats
+}
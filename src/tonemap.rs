@@ -0,0 +1,63 @@
+use std::str::FromStr;
+use crate::color::Color;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ToneMapper {
+    // Recorta a [0, 1]: equivale a no hacer tone mapping
+    Clamp,
+    Reinhard,
+    // Aproximación de Narkowicz de la curva filmica ACES
+    Aces,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ToneMapping {
+    pub operator: ToneMapper,
+    pub exposure: f32,
+}
+
+impl Default for ToneMapping {
+    fn default() -> Self {
+        ToneMapping {
+            operator: ToneMapper::Clamp,
+            exposure: 1.0,
+        }
+    }
+}
+
+impl ToneMapping {
+    // Color lineal HDR -> píxel 0xRRGGBB en sRGB
+    pub fn apply(&self, color: Color) -> u32 {
+        let exposed = color * self.exposure;
+        let map = |c: f32| match self.operator {
+            ToneMapper::Clamp => c,
+            ToneMapper::Reinhard => c / (1.0 + c),
+            ToneMapper::Aces => (c * (2.51 * c + 0.03)) / (c * (2.43 * c + 0.59) + 0.14),
+        };
+
+        Color::from_float(map(exposed.r), map(exposed.g), map(exposed.b)).to_hex()
+    }
+}
+
+impl ToneMapper {
+    pub fn next(self) -> Self {
+        match self {
+            ToneMapper::Clamp => ToneMapper::Reinhard,
+            ToneMapper::Reinhard => ToneMapper::Aces,
+            ToneMapper::Aces => ToneMapper::Clamp,
+        }
+    }
+}
+
+impl FromStr for ToneMapper {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "clamp" | "none" => Ok(ToneMapper::Clamp),
+            "reinhard" => Ok(ToneMapper::Reinhard),
+            "aces" => Ok(ToneMapper::Aces),
+            _ => Err(format!("Unknown tone mapper: {}", s)),
+        }
+    }
+}
@@ -2,84 +2,240 @@ use nalgebra_glm::{Vec2, Vec3};
 use crate::fragment::Fragment;
 use crate::vertex::Vertex;
 use crate::color::Color;
+use crate::framebuffer::MAX_SAMPLES;
 
-pub fn triangle(v1: &Vertex, v2: &Vertex, v3: &Vertex) -> Vec<Fragment> {
-    let mut fragments = Vec::new();
+// Rectángulo de píxeles con límites inclusivos
+#[derive(Debug, Clone, Copy)]
+pub struct Rect {
+    pub min_x: i32,
+    pub min_y: i32,
+    pub max_x: i32,
+    pub max_y: i32,
+}
+
+impl Rect {
+    pub fn intersect(&self, other: &Rect) -> Option<Rect> {
+        let rect = Rect {
+            min_x: self.min_x.max(other.min_x),
+            min_y: self.min_y.max(other.min_y),
+            max_x: self.max_x.min(other.max_x),
+            max_y: self.max_y.min(other.max_y),
+        };
+
+        if rect.min_x > rect.max_x || rect.min_y > rect.max_y {
+            None
+        } else {
+            Some(rect)
+        }
+    }
+}
+
+// Precisión de subpíxel: las posiciones se redondean a 1/256 de píxel
+const SUBPIXEL_BITS: u32 = 8;
+const SUBPIXEL_ONE: i64 = 1 << SUBPIXEL_BITS;
+const SUBPIXEL_HALF: i64 = SUBPIXEL_ONE / 2;
+
+fn to_fixed(value: f32) -> i64 {
+    (value * SUBPIXEL_ONE as f32).round() as i64
+}
+
+// Función de arista E(p) = a * (p.x - x0) + b * (p.y - y0), orientada para
+// que el interior del triángulo sea positivo
+struct Edge {
+    a: i64,
+    b: i64,
+    bias: i64,
+}
+
+impl Edge {
+    fn new(x0: i64, y0: i64, x1: i64, y1: i64, orientation: i64) -> Self {
+        let a = (y0 - y1) * orientation;
+        let b = (x1 - x0) * orientation;
+
+        // Regla top-left: un píxel justo sobre la arista solo se cuenta si la
+        // arista es izquierda (interior a la derecha) o superior (horizontal,
+        // interior abajo)
+        let top_left = a > 0 || (a == 0 && b > 0);
+
+        Edge { a, b, bias: if top_left { 0 } else { -1 } }
+    }
+
+    // Longitud del gradiente, para pasar el valor de la arista a distancia en píxeles
+    fn inverse_length(&self) -> f32 {
+        1.0 / ((self.a * self.a + self.b * self.b) as f32).sqrt() / SUBPIXEL_ONE as f32
+    }
+
+    fn evaluate(&self, x0: i64, y0: i64, px: i64, py: i64) -> i64 {
+        self.a * (px - x0) + self.b * (py - y0)
+    }
+}
+
+// Entrega a `emit` cada fragmento dentro de `clip` a medida que se genera,
+// sin reservar memoria por triángulo. `samples` son las posiciones de muestra
+// dentro del píxel (en 1/16 de píxel); el fragmento se emite si cubre alguna
+// y sus atributos se interpolan en el centro del píxel.
+pub fn triangle<F: FnMut(&Fragment)>(v1: &Vertex, v2: &Vertex, v3: &Vertex, clip: &Rect, samples: &[(i32, i32)], mut emit: F) {
 
     let a = v1.transformed_position;
     let b = v2.transformed_position;
     let c = v3.transformed_position;
 
-    let (min_x, min_y, max_x, max_y) = calculate_bounding_box(&a, &b, &c);
+    let Some(bounds) = bounding_box(&a, &b, &c).intersect(clip) else {
+        return;
+    };
+    let Rect { min_x, min_y, max_x, max_y } = bounds;
+
+    let (ax, ay) = (to_fixed(a.x), to_fixed(a.y));
+    let (bx, by) = (to_fixed(b.x), to_fixed(b.y));
+    let (cx, cy) = (to_fixed(c.x), to_fixed(c.y));
+
+    let signed_area = (bx - ax) * (cy - ay) - (by - ay) * (cx - ax);
+    if signed_area == 0 {
+        return;
+    }
+    let orientation = signed_area.signum();
+    let area = signed_area.abs() as f32;
+
+    // Cada arista corresponde al vértice opuesto: e1 -> v1, e2 -> v2, e3 -> v3
+    let e1 = Edge::new(bx, by, cx, cy, orientation);
+    let e2 = Edge::new(cx, cy, ax, ay, orientation);
+    let e3 = Edge::new(ax, ay, bx, by, orientation);
+
+    // Desplazamiento de cada arista desde el centro del píxel hasta cada muestra
+    let sample_count = samples.len().min(MAX_SAMPLES);
+    let mut offsets = [[0i64; 3]; MAX_SAMPLES];
+    for (offset, &(sx, sy)) in offsets.iter_mut().zip(samples) {
+        let dx = sx as i64 * SUBPIXEL_ONE / 16 - SUBPIXEL_HALF;
+        let dy = sy as i64 * SUBPIXEL_ONE / 16 - SUBPIXEL_HALF;
+        *offset = [
+            e1.a * dx + e1.b * dy,
+            e2.a * dx + e2.b * dy,
+            e3.a * dx + e3.b * dy,
+        ];
+    }
+
+    // Valores en el centro del primer píxel; luego se avanza incrementalmente
+    let start_x = min_x as i64 * SUBPIXEL_ONE + SUBPIXEL_HALF;
+    let start_y = min_y as i64 * SUBPIXEL_ONE + SUBPIXEL_HALF;
+    let origin = (
+        e1.evaluate(bx, by, start_x, start_y),
+        e2.evaluate(cx, cy, start_x, start_y),
+        e3.evaluate(ax, ay, start_x, start_y),
+    );
+    let (mut row1, mut row2, mut row3) = origin;
+
+    let inverse_lengths = [e1.inverse_length(), e2.inverse_length(), e3.inverse_length()];
 
-    let light_dir = Vec3::new(0.0, 0.0, 1.0);
+    // Normal geométrica, orientada hacia el mismo lado que las normales de los vértices
+    let mut face_normal = (v2.position - v1.position).cross(&(v3.position - v1.position));
+    if face_normal.dot(&(v1.normal + v2.normal + v3.normal)) < 0.0 {
+        face_normal = -face_normal;
+    }
+    let face_normal = face_normal.try_normalize(f32::EPSILON).unwrap_or(v1.normal);
+
+    // 1/w de cada vértice para la interpolación con corrección de perspectiva
+    let inv_w1 = 1.0 / v1.clip_position.w;
+    let inv_w2 = 1.0 / v2.clip_position.w;
+    let inv_w3 = 1.0 / v3.clip_position.w;
+
+    // UV con corrección de perspectiva en el centro de cualquier píxel, aunque
+    // quede fuera del triángulo (como los píxeles auxiliares de un quad en GPU)
+    let uv_at = |px: i32, py: i32| {
+        let dx = (px - min_x) as i64 * SUBPIXEL_ONE;
+        let dy = (py - min_y) as i64 * SUBPIXEL_ONE;
+        let w1 = (origin.0 + e1.a * dx + e1.b * dy) as f32 / area;
+        let w2 = (origin.1 + e2.a * dx + e2.b * dy) as f32 / area;
+        let w3 = (origin.2 + e3.a * dx + e3.b * dy) as f32 / area;
+        let (p1, p2, p3) = perspective_weights(w1 * inv_w1, w2 * inv_w2, w3 * inv_w3);
+        v1.tex_coords.xy() * p1 + v2.tex_coords.xy() * p2 + v3.tex_coords.xy() * p3
+    };
 
     for y in min_y..=max_y {
+        let (mut edge1, mut edge2, mut edge3) = (row1, row2, row3);
+
         for x in min_x..=max_x {
-            let point = Vec3::new(x as f32 + 0.5, y as f32 + 0.5, 0.0);
-            
-            if let Some((w1, w2, w3)) = barycentric_coordinates(&point, &a, &b, &c) {
-                if w1 >= 0.0 && w1 <= 1.0 && 
-                   w2 >= 0.0 && w2 <= 1.0 && 
-                   w3 >= 0.0 && w3 <= 1.0 &&
-                   (w1 + w2 + w3 - 1.0).abs() < 0.001 {
-                    
-                    let depth = w1 * a.z + w2 * b.z + w3 * c.z;
-                    
-                    let normal = (v1.transformed_normal * w1 + 
-                                 v2.transformed_normal * w2 + 
-                                 v3.transformed_normal * w3).normalize();
-                    
-                    let vertex_position = v1.position * w1 + v2.position * w2 + v3.position * w3;
-                    
-                    let intensity = normal.dot(&light_dir).max(0.0);
-                    
-                    fragments.push(Fragment::new(
-                        Vec2::new(x as f32, y as f32),
-                        depth,
-                        Color::new(255, 255, 255),
-                        normal,
-                        vertex_position,
-                        intensity,
-                    ));
+            let mut coverage = 0u32;
+            let mut sample_depths = [0.0; MAX_SAMPLES];
+            for (sample, offset) in offsets.iter().take(sample_count).enumerate() {
+                let s1 = edge1 + offset[0];
+                let s2 = edge2 + offset[1];
+                let s3 = edge3 + offset[2];
+                if s1 + e1.bias >= 0 && s2 + e2.bias >= 0 && s3 + e3.bias >= 0 {
+                    coverage |= 1 << sample;
+                    // z/w ya es lineal en pantalla, así que la profundidad usa
+                    // las baricéntricas de pantalla directamente
+                    sample_depths[sample] = (s1 as f32 / area) * a.z + (s2 as f32 / area) * b.z + (s3 as f32 / area) * c.z;
                 }
             }
-        }
-    }
 
-    fragments
-}
+            if coverage != 0 {
+                let w1 = edge1 as f32 / area;
+                let w2 = edge2 as f32 / area;
+                let w3 = edge3 as f32 / area;
 
-fn calculate_bounding_box(v1: &Vec3, v2: &Vec3, v3: &Vec3) -> (i32, i32, i32, i32) {
-    let min_x = v1.x.min(v2.x).min(v3.x).floor() as i32;
-    let min_y = v1.y.min(v2.y).min(v3.y).floor() as i32;
-    let max_x = v1.x.max(v2.x).max(v3.x).ceil() as i32;
-    let max_y = v1.y.max(v2.y).max(v3.y).ceil() as i32;
+                let depth = w1 * a.z + w2 * b.z + w3 * c.z;
 
-    (min_x, min_y, max_x, max_y)
-}
+                // El resto de atributos se interpola con pesos corregidos por w
+                let (p1, p2, p3) = perspective_weights(w1 * inv_w1, w2 * inv_w2, w3 * inv_w3);
+
+                let normal = (v1.transformed_normal * p1 +
+                             v2.transformed_normal * p2 +
+                             v3.transformed_normal * p3).normalize();
 
-fn barycentric_coordinates(p: &Vec3, a: &Vec3, b: &Vec3, c: &Vec3) -> Option<(f32, f32, f32)> {
-    let v0 = Vec3::new(b.x - a.x, b.y - a.y, 0.0);
-    let v1 = Vec3::new(c.x - a.x, c.y - a.y, 0.0);
-    let v2 = Vec3::new(p.x - a.x, p.y - a.y, 0.0);
+                let vertex_position = v1.position * p1 + v2.position * p2 + v3.position * p3;
+                let world_position = v1.world_position * p1 + v2.world_position * p2 + v3.world_position * p3;
 
-    let dot00 = v0.dot(&v0);
-    let dot01 = v0.dot(&v1);
-    let dot11 = v1.dot(&v1);
-    let dot20 = v2.dot(&v0);
-    let dot21 = v2.dot(&v1);
+                let color = v1.color * p1 + v2.color * p2 + v3.color * p3;
 
-    let denom = dot00 * dot11 - dot01 * dot01;
+                let mut fragment = Fragment::new(
+                    Vec2::new(x as f32, y as f32),
+                    depth,
+                    Color::from_float(color.x, color.y, color.z),
+                    normal,
+                    vertex_position,
+                    world_position,
+                );
+                fragment.coverage = coverage;
+                fragment.sample_depths = sample_depths;
+                fragment.tex_coords = v1.tex_coords.xy() * p1 + v2.tex_coords.xy() * p2 + v3.tex_coords.xy() * p3;
+                // Derivadas gruesas por quad de 2x2, para elegir el nivel de mipmap
+                let (quad_x, quad_y) = (x & !1, y & !1);
+                let quad_uv = uv_at(quad_x, quad_y);
+                let uv_dx = uv_at(quad_x + 1, quad_y) - quad_uv;
+                let uv_dy = uv_at(quad_x, quad_y + 1) - quad_uv;
+                if uv_dx.iter().chain(uv_dy.iter()).all(|d| d.is_finite()) {
+                    fragment.uv_dx = uv_dx;
+                    fragment.uv_dy = uv_dy;
+                }
+                fragment.barycentric = Vec3::new(p1, p2, p3);
+                fragment.face_normal = face_normal;
+                fragment.edge_distance = (edge1 as f32 * inverse_lengths[0])
+                    .min(edge2 as f32 * inverse_lengths[1])
+                    .min(edge3 as f32 * inverse_lengths[2]);
+                emit(&fragment);
+            }
 
-    if denom.abs() < 1e-8 {
-        return None;
+            edge1 += e1.a * SUBPIXEL_ONE;
+            edge2 += e2.a * SUBPIXEL_ONE;
+            edge3 += e3.a * SUBPIXEL_ONE;
+        }
+
+        row1 += e1.b * SUBPIXEL_ONE;
+        row2 += e2.b * SUBPIXEL_ONE;
+        row3 += e3.b * SUBPIXEL_ONE;
     }
+}
 
-    let inv_denom = 1.0 / denom;
-    let w2 = (dot11 * dot20 - dot01 * dot21) * inv_denom;
-    let w3 = (dot00 * dot21 - dot01 * dot20) * inv_denom;
-    let w1 = 1.0 - w2 - w3;
+fn perspective_weights(p1: f32, p2: f32, p3: f32) -> (f32, f32, f32) {
+    let sum = p1 + p2 + p3;
+    (p1 / sum, p2 / sum, p3 / sum)
+}
 
-    Some((w1, w2, w3))
-}
\ No newline at end of file
+pub fn bounding_box(v1: &Vec3, v2: &Vec3, v3: &Vec3) -> Rect {
+    Rect {
+        min_x: v1.x.min(v2.x).min(v3.x).floor() as i32,
+        min_y: v1.y.min(v2.y).min(v3.y).floor() as i32,
+        max_x: v1.x.max(v2.x).max(v3.x).ceil() as i32,
+        max_y: v1.y.max(v2.y).max(v3.y).ceil() as i32,
+    }
+}
@@ -1,4 +1,4 @@
-use nalgebra_glm::Vec3;
+use nalgebra_glm::{Vec3, Vec4};
 
 #[derive(Debug, Clone)]
 pub struct Vertex {
@@ -6,6 +6,9 @@ pub struct Vertex {
     pub normal: Vec3,
     pub tex_coords: Vec3,
     pub color: Vec3,
+    pub clip_position: Vec4,
+    // Posición en espacio de mundo, para la iluminación
+    pub world_position: Vec3,
     pub transformed_position: Vec3,
     pub transformed_normal: Vec3,
 }
@@ -17,6 +20,8 @@ impl Vertex {
             normal,
             tex_coords,
             color: Vec3::new(1.0, 1.0, 1.0),
+            clip_position: Vec4::zeros(),
+            world_position: Vec3::zeros(),
             transformed_position: Vec3::zeros(),
             transformed_normal: Vec3::zeros(),
         }